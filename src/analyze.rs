@@ -0,0 +1,254 @@
+// Chapter 6: Parse != Analyze
+// The parser records CTE structure; the analyzer resolves self-references.
+
+use crate::ast::{Query, SelectStmt, Span, Statement, With, CTE};
+
+/// A single well-formedness problem found during analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+impl Diagnostic {
+    fn new(message: impl Into<String>, span: Option<Span>) -> Self {
+        Diagnostic {
+            message: message.into(),
+            span,
+        }
+    }
+}
+
+/// A CTE name bound to its index within the enclosing `WITH`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding<'a> {
+    pub name: &'a str,
+    pub index: usize,
+    pub recursive: bool,
+}
+
+/// Result of analysing a statement: the resolved CTE bindings plus any
+/// diagnostics. An empty `diagnostics` list means the statement is well-formed.
+#[derive(Debug, Clone, Default)]
+pub struct Analysis<'a> {
+    pub bindings: Vec<Binding<'a>>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Analyse a statement, validating recursive-CTE well-formedness.
+pub fn analyze<'a>(stmt: &Statement<'a>) -> Analysis<'a> {
+    let mut analysis = Analysis::default();
+    match stmt {
+        Statement::Query(query) => analyze_query(query, &mut Vec::new(), &mut analysis),
+        // INSERT ... SELECT can embed a query whose CTEs still need checking;
+        // the other DML forms carry no CTE structure to analyse.
+        Statement::Insert(insert) => {
+            if let crate::ast::InsertSource::Query(query) = &insert.source {
+                analyze_query(query, &mut Vec::new(), &mut analysis);
+            }
+        }
+        Statement::Update(_) | Statement::Delete(_) | Statement::Error => {}
+    }
+    analysis
+}
+
+fn analyze_query<'a>(
+    query: &Query<'a>,
+    scope: &mut Vec<&'a str>,
+    analysis: &mut Analysis<'a>,
+) {
+    match query {
+        Query::Select(_) => {}
+        Query::Union { left, right, .. } => {
+            analyze_query(left, scope, analysis);
+            analyze_query(right, scope, analysis);
+        }
+        Query::With { with, query } => {
+            let scope_len = scope.len();
+            analyze_with(with, scope, analysis);
+            analyze_query(query, scope, analysis);
+            // CTE names only remain visible within their `WITH`.
+            scope.truncate(scope_len);
+        }
+    }
+}
+
+fn analyze_with<'a>(with: &With<'a>, scope: &mut Vec<&'a str>, analysis: &mut Analysis<'a>) {
+    let mut seen: Vec<&str> = Vec::new();
+
+    for (index, cte) in with.ctes.iter().enumerate() {
+        // Duplicate names within one WITH.
+        if seen.contains(&cte.name) {
+            analysis.diagnostics.push(Diagnostic::new(
+                format!("duplicate CTE name `{}` in WITH clause", cte.name),
+                Some(cte.span),
+            ));
+        }
+        seen.push(cte.name);
+
+        // A recursive CTE can see itself; later CTEs see earlier ones.
+        if with.recursive {
+            scope.push(cte.name);
+        }
+
+        check_cte(cte, with.recursive, analysis);
+
+        if !with.recursive {
+            scope.push(cte.name);
+        }
+
+        analysis.bindings.push(Binding {
+            name: cte.name,
+            index,
+            recursive: with.recursive,
+        });
+
+        // Analyse nested WITHs inside the CTE body.
+        analyze_query(&cte.query, scope, analysis);
+    }
+}
+
+fn check_cte<'a>(cte: &CTE<'a>, recursive: bool, analysis: &mut Analysis<'a>) {
+    let mut refs = Vec::new();
+    collect_table_refs(&cte.query, &mut refs);
+    let self_referential = refs.contains(&cte.name);
+
+    if self_referential && !recursive {
+        analysis.diagnostics.push(Diagnostic::new(
+            format!("CTE `{}` references itself but WITH is not RECURSIVE", cte.name),
+            Some(cte.span),
+        ));
+    }
+
+    if recursive && self_referential {
+        match cte.query.as_ref() {
+            Query::Union { left, right, all } => {
+                let mut anchor_refs = Vec::new();
+                collect_table_refs(left, &mut anchor_refs);
+                if anchor_refs.contains(&cte.name) {
+                    analysis.diagnostics.push(Diagnostic::new(
+                        format!(
+                            "recursive CTE `{}` references itself in its anchor term",
+                            cte.name
+                        ),
+                        Some(cte.span),
+                    ));
+                }
+
+                let mut recursive_refs = Vec::new();
+                collect_table_refs(right, &mut recursive_refs);
+                if !recursive_refs.contains(&cte.name) {
+                    analysis.diagnostics.push(Diagnostic::new(
+                        format!(
+                            "recursive CTE `{}` does not reference itself in its recursive term",
+                            cte.name
+                        ),
+                        Some(cte.span),
+                    ));
+                }
+
+                if !all {
+                    analysis.diagnostics.push(Diagnostic::new(
+                        format!("recursive CTE `{}` should use UNION ALL", cte.name),
+                        Some(cte.span),
+                    ));
+                }
+            }
+            _ => analysis.diagnostics.push(Diagnostic::new(
+                format!("recursive CTE `{}` lacks a UNION anchor/recursive split", cte.name),
+                Some(cte.span),
+            )),
+        }
+    }
+
+    // Column-count mismatch against the declared column list.
+    if let Some(columns) = &cte.columns {
+        if let Some(arity) = projection_arity(&cte.query) {
+            if arity != columns.len() {
+                analysis.diagnostics.push(Diagnostic::new(
+                    format!(
+                        "CTE `{}` declares {} column(s) but its query projects {}",
+                        cte.name,
+                        columns.len(),
+                        arity
+                    ),
+                    Some(cte.span),
+                ));
+            }
+        }
+    }
+}
+
+/// Arity of the leading SELECT's projection, if determinable.
+fn projection_arity(query: &Query) -> Option<usize> {
+    match query {
+        Query::Select(select) => Some(leading_projection(select)),
+        Query::Union { left, .. } => projection_arity(left),
+        Query::With { query, .. } => projection_arity(query),
+    }
+}
+
+fn leading_projection(select: &SelectStmt) -> usize {
+    select.projection.len()
+}
+
+/// Collect every `TableRef` name reachable from a query.
+fn collect_table_refs<'a>(query: &Query<'a>, out: &mut Vec<&'a str>) {
+    match query {
+        Query::Select(select) => {
+            // The current grammar keeps table refs in the FROM clause only.
+            if let Some(from) = &select.from {
+                out.push(from.name);
+            }
+        }
+        Query::Union { left, right, .. } => {
+            collect_table_refs(left, out);
+            collect_table_refs(right, out);
+        }
+        Query::With { with, query } => {
+            for cte in &with.ctes {
+                collect_table_refs(&cte.query, out);
+            }
+            collect_table_refs(query, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Backtrace;
+    use crate::parser::Parser;
+    use crate::token::tokenize;
+
+    fn analyze_sql(sql: &str) -> Vec<String> {
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, sql);
+        let stmt = parser.parse_statement().expect("should parse");
+        analyze(&stmt)
+            .diagnostics
+            .into_iter()
+            .map(|d| d.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_self_reference_requires_recursive() {
+        let diags = analyze_sql("WITH t AS (SELECT 1 FROM t) SELECT 1 FROM t");
+        assert!(diags.iter().any(|m| m.contains("not RECURSIVE")));
+    }
+
+    #[test]
+    fn test_duplicate_cte_names() {
+        let diags =
+            analyze_sql("WITH t AS (SELECT 1), t AS (SELECT 2) SELECT 1 FROM t");
+        assert!(diags.iter().any(|m| m.contains("duplicate CTE name")));
+    }
+
+    #[test]
+    fn test_column_count_mismatch() {
+        let diags = analyze_sql("WITH t(a, b) AS (SELECT 1) SELECT 1 FROM t");
+        assert!(diags.iter().any(|m| m.contains("column(s)")));
+    }
+}
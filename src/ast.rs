@@ -5,18 +5,153 @@ use crate::expr::Expr;
 use colored::*;
 use std::fmt;
 
+/// A single source position: byte `offset` plus the 1-based `line`/`column`
+/// it resolves to.
+///
+/// The line/column are computed once while tokenizing (by counting newlines as
+/// the lexer advances), so a span can be rendered without rescanning the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Location {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    pub fn new(offset: usize, line: usize, column: usize) -> Self {
+        Location {
+            offset,
+            line,
+            column,
+        }
+    }
+}
+
+/// A half-open source range `[start, end)` carrying both byte offsets and
+/// line/column positions.
+///
+/// Spans are captured from token locations so diagnostics and tooling can point
+/// at "this CTE" or "this column reference" in the input — down to the exact
+/// byte and line/column range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+impl Span {
+    pub fn new(start: Location, end: Location) -> Self {
+        Span { start, end }
+    }
+
+    /// The smallest span covering both `self` and `other`, compared by byte
+    /// offset.
+    pub fn union(self, other: Span) -> Span {
+        let start = if self.start.offset <= other.start.offset {
+            self.start
+        } else {
+            other.start
+        };
+        let end = if self.end.offset >= other.end.offset {
+            self.end
+        } else {
+            other.end
+        };
+        Span { start, end }
+    }
+
+    /// The half-open byte range this span covers, for slicing the source.
+    pub fn byte_range(&self) -> std::ops::Range<usize> {
+        self.start.offset..self.end.offset
+    }
+
+    /// The span's `(line, column)` start and end, already resolved during
+    /// tokenization.
+    pub fn line_col_range(&self) -> ((usize, usize), (usize, usize)) {
+        (
+            (self.start.line, self.start.column),
+            (self.end.line, self.end.column),
+        )
+    }
+}
+
+/// A node paired with the source span it was parsed from.
+///
+/// An alternative to a per-struct `span` field for nodes (like the borrowed
+/// `Expr` tree) where threading a field through every variant is undesirable.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
+}
+
+impl<T: fmt::Display> fmt::Display for Spanned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Display is span-agnostic: pretty-printing is unchanged.
+        write!(f, "{}", self.node)
+    }
+}
+
 /// SQL Statement types
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Statement<'a> {
     Query(Query<'a>),
-    // Can add Insert, Update, Delete etc.
+    Insert(#[cfg_attr(feature = "serde", serde(borrow))] InsertStmt<'a>),
+    Update(#[cfg_attr(feature = "serde", serde(borrow))] UpdateStmt<'a>),
+    Delete(#[cfg_attr(feature = "serde", serde(borrow))] DeleteStmt<'a>),
+    /// Placeholder for a statement that failed to parse, produced by the
+    /// error-recovering parser so diagnostics can still be collected.
+    Error,
+}
+
+/// `INSERT INTO t (cols...) VALUES (...)` or `INSERT INTO t SELECT ...`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InsertStmt<'a> {
+    pub table: &'a str,
+    pub columns: Option<Vec<&'a str>>,
+    pub source: InsertSource<'a>,
+    pub span: Span,
+}
+
+/// Where an INSERT's rows come from.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InsertSource<'a> {
+    Values(#[cfg_attr(feature = "serde", serde(borrow))] Vec<Vec<Expr<'a>>>),
+    Query(Box<Query<'a>>),
+}
+
+/// `UPDATE t SET col = expr, ... WHERE ...`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UpdateStmt<'a> {
+    pub table: &'a str,
+    pub assignments: Vec<(&'a str, Expr<'a>)>,
+    pub where_clause: Option<Expr<'a>>,
+    pub span: Span,
+}
+
+/// `DELETE FROM t WHERE ...`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeleteStmt<'a> {
+    pub table: &'a str,
+    pub where_clause: Option<Expr<'a>>,
+    pub span: Span,
 }
 
 /// Query types
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Query<'a> {
-    Select(Box<SelectStmt<'a>>),
+    Select(#[cfg_attr(feature = "serde", serde(borrow))] Box<SelectStmt<'a>>),
     With {
+        #[cfg_attr(feature = "serde", serde(borrow))]
         with: With<'a>,
         query: Box<Query<'a>>,
     },
@@ -29,34 +164,78 @@ pub enum Query<'a> {
 
 /// SELECT statement
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SelectStmt<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub projection: Vec<Expr<'a>>,
     pub from: Option<TableRef<'a>>,
     pub where_clause: Option<Expr<'a>>,
+    pub span: Span,
 }
 
 /// Table reference
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableRef<'a> {
     pub name: &'a str,
     pub alias: Option<&'a str>,
+    pub span: Span,
 }
 
 /// WITH clause containing CTEs
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct With<'a> {
     pub recursive: bool,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub ctes: Vec<CTE<'a>>,
 }
 
 /// Common Table Expression (CTE)
 /// The key insight: This is just structure, no recursive parsing needed!
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::upper_case_acronyms)]
 pub struct CTE<'a> {
     pub name: &'a str,
     pub columns: Option<Vec<&'a str>>,
     pub query: Box<Query<'a>>, // Just a normal query!
+    pub span: Span,
+}
+
+impl<'a> Statement<'a> {
+    /// The source span covering the whole statement.
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Query(q) => q.span(),
+            Statement::Insert(s) => s.span,
+            Statement::Update(s) => s.span,
+            Statement::Delete(s) => s.span,
+            Statement::Error => Span::default(),
+        }
+    }
+}
+
+impl<'a> Query<'a> {
+    /// The source span covering this query, derived bottom-up from its nodes.
+    pub fn span(&self) -> Span {
+        match self {
+            Query::Select(s) => s.span,
+            Query::Union { left, right, .. } => left.span().union(right.span()),
+            Query::With { with, query } => with.span().union(query.span()),
+        }
+    }
+}
+
+impl<'a> With<'a> {
+    /// The source span covering all of this `WITH` clause's CTEs.
+    pub fn span(&self) -> Span {
+        self.ctes
+            .iter()
+            .map(|cte| cte.span)
+            .reduce(Span::union)
+            .unwrap_or_default()
+    }
 }
 
 /// Demonstrate the CTE insight
@@ -107,7 +286,66 @@ impl<'a> fmt::Display for Statement<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Statement::Query(q) => write!(f, "{}", q),
+            Statement::Insert(s) => write!(f, "{}", s),
+            Statement::Update(s) => write!(f, "{}", s),
+            Statement::Delete(s) => write!(f, "{}", s),
+            Statement::Error => write!(f, "<error>"),
+        }
+    }
+}
+
+impl<'a> fmt::Display for InsertStmt<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "INSERT INTO {}", self.table)?;
+        if let Some(columns) = &self.columns {
+            write!(f, " ({})", columns.join(", "))?;
+        }
+        match &self.source {
+            InsertSource::Values(rows) => {
+                write!(f, " VALUES ")?;
+                for (i, row) in rows.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "(")?;
+                    for (j, expr) in row.iter().enumerate() {
+                        if j > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}", expr)?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            InsertSource::Query(query) => write!(f, " {}", query),
+        }
+    }
+}
+
+impl<'a> fmt::Display for UpdateStmt<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UPDATE {} SET ", self.table)?;
+        for (i, (col, expr)) in self.assignments.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} = {}", col, expr)?;
+        }
+        if let Some(where_clause) = &self.where_clause {
+            write!(f, " WHERE {}", where_clause)?;
         }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for DeleteStmt<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DELETE FROM {}", self.table)?;
+        if let Some(where_clause) = &self.where_clause {
+            write!(f, " WHERE {}", where_clause)?;
+        }
+        Ok(())
     }
 }
 
@@ -119,13 +357,11 @@ impl<'a> fmt::Display for Query<'a> {
                 write!(f, "{} {}", with, query)
             }
             Query::Union { left, all, right } => {
-                write!(
-                    f,
-                    "{} UNION {} {}",
-                    left,
-                    if *all { "ALL" } else { "" },
-                    right
-                )
+                if *all {
+                    write!(f, "{} UNION ALL {}", left, right)
+                } else {
+                    write!(f, "{} UNION {}", left, right)
+                }
             }
         }
     }
@@ -0,0 +1,161 @@
+// Chapter 7: One Grammar, Many Dialects
+// Keyword sets and identifier rules vary by vendor; factor them behind a trait.
+
+/// Controls dialect-specific lexing and parsing decisions.
+///
+/// Defaults describe ANSI-ish SQL; concrete dialects override only what differs.
+pub trait Dialect {
+    /// Is `ch` allowed to start an unquoted identifier?
+    fn is_identifier_start(&self, ch: char) -> bool {
+        ch.is_ascii_alphabetic() || ch == '_'
+    }
+
+    /// Is `ch` allowed inside an unquoted identifier?
+    fn is_identifier_part(&self, ch: char) -> bool {
+        ch.is_ascii_alphanumeric() || ch == '_'
+    }
+
+    /// Does the dialect tolerate a trailing comma before `)` or a clause keyword?
+    fn supports_trailing_commas(&self) -> bool {
+        false
+    }
+
+    /// Does the dialect accept an explicit `ROW(...)` constructor in a `VALUES`
+    /// list (e.g. PostgreSQL's `VALUES ROW(1, 2)`)? ANSI/generic write the bare
+    /// `(1, 2)` tuple instead.
+    fn supports_explicit_row(&self) -> bool {
+        false
+    }
+
+    /// Delimiter used to quote identifiers (e.g. `` ` `` for MySQL, `"` for ANSI),
+    /// or `None` if the dialect has no quoted-identifier syntax.
+    fn identifier_quote(&self) -> Option<char> {
+        None
+    }
+
+    /// Is `keyword` (case-insensitive) reserved and therefore not a plain identifier?
+    fn is_reserved_keyword(&self, keyword: &str) -> bool {
+        self.keywords()
+            .contains(&keyword.to_uppercase().as_str())
+    }
+
+    /// The reserved-word set for this dialect, used both to reject keywords as
+    /// plain identifiers and to rank keyword suggestions.
+    fn keywords(&self) -> &'static [&'static str] {
+        RESERVED_KEYWORDS
+    }
+
+    /// Line-comment prefixes the tokenizer should skip. ANSI only has `--`;
+    /// MySQL additionally treats `#` to end-of-line as a comment.
+    fn line_comment_prefixes(&self) -> &'static [&'static str] {
+        &["--"]
+    }
+
+    /// Does the dialect support dollar-quoted string literals (PostgreSQL's
+    /// `$$...$$` / `$tag$...$tag$`)?
+    fn supports_dollar_quoted_strings(&self) -> bool {
+        false
+    }
+
+    /// Does the dialect support the `::` postfix cast operator (e.g.
+    /// PostgreSQL's `age::text`)?
+    fn supports_cast_operator(&self) -> bool {
+        false
+    }
+}
+
+/// Keywords reserved across all built-in dialects.
+const RESERVED_KEYWORDS: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "WITH", "RECURSIVE", "AS", "UNION", "ALL", "AND", "OR", "NOT", "IN",
+    "BETWEEN", "DISTINCT", "INSERT", "UPDATE", "DELETE",
+];
+
+/// Permissive dialect with ANSI defaults and no quoted-identifier syntax.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenericDialect;
+impl Dialect for GenericDialect {}
+
+/// ANSI SQL: double-quoted identifiers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnsiDialect;
+impl Dialect for AnsiDialect {
+    fn identifier_quote(&self) -> Option<char> {
+        Some('"')
+    }
+}
+
+/// MySQL: backtick-quoted identifiers and `#` line comments.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MySqlDialect;
+impl Dialect for MySqlDialect {
+    fn identifier_quote(&self) -> Option<char> {
+        Some('`')
+    }
+
+    fn line_comment_prefixes(&self) -> &'static [&'static str] {
+        &["--", "#"]
+    }
+}
+
+/// PostgreSQL: double-quoted identifiers, dollar-quoted strings, `::` casts,
+/// and explicit `ROW(...)` constructors.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostgreSqlDialect;
+impl Dialect for PostgreSqlDialect {
+    fn identifier_quote(&self) -> Option<char> {
+        Some('"')
+    }
+
+    fn supports_explicit_row(&self) -> bool {
+        true
+    }
+
+    fn supports_dollar_quoted_strings(&self) -> bool {
+        true
+    }
+
+    fn supports_cast_operator(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifier_quotes_differ() {
+        assert_eq!(GenericDialect.identifier_quote(), None);
+        assert_eq!(MySqlDialect.identifier_quote(), Some('`'));
+        assert_eq!(PostgreSqlDialect.identifier_quote(), Some('"'));
+    }
+
+    #[test]
+    fn test_reserved_keywords() {
+        assert!(GenericDialect.is_reserved_keyword("select"));
+        assert!(!GenericDialect.is_reserved_keyword("users"));
+    }
+
+    #[test]
+    fn test_explicit_row_is_postgres_only() {
+        assert!(PostgreSqlDialect.supports_explicit_row());
+        assert!(!GenericDialect.supports_explicit_row());
+        assert!(!MySqlDialect.supports_explicit_row());
+    }
+
+    #[test]
+    fn test_mysql_adds_hash_comment() {
+        assert!(MySqlDialect.line_comment_prefixes().contains(&"#"));
+        assert!(!GenericDialect.line_comment_prefixes().contains(&"#"));
+    }
+
+    #[test]
+    fn test_dollar_quotes_and_casts_are_postgres_only() {
+        assert!(PostgreSqlDialect.supports_dollar_quoted_strings());
+        assert!(PostgreSqlDialect.supports_cast_operator());
+        assert!(!GenericDialect.supports_dollar_quoted_strings());
+        assert!(!GenericDialect.supports_cast_operator());
+        assert!(!MySqlDialect.supports_dollar_quoted_strings());
+        assert!(!MySqlDialect.supports_cast_operator());
+    }
+}
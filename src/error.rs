@@ -1,10 +1,11 @@
 // Chapter 2: Error Recovery That Actually Helps
 // This shows how RefCell enables shared mutable state for error tracking
 
+use crate::ast::{Location, Span};
+use crate::dialect::Dialect;
 use colored::*;
 use std::cell::RefCell;
 use std::fmt;
-use strsim::jaro_winkler;
 
 /// The furthest error tracking system
 /// RefCell allows us to track errors through immutable parser methods
@@ -63,8 +64,14 @@ impl Backtrace {
         }
     }
 
-    /// Get the best error message with suggestions
-    pub fn get_error(&self, input: &str) -> ParseError {
+    /// Get the best error message with suggestions, ranking candidates against
+    /// `dialect`'s reserved-word set.
+    ///
+    /// `suggestion`/`suggestions` are entirely governed by `suggest_keywords`'s
+    /// Jaro-Winkler ranking of `inner.expected` (and, as a fallback, the full
+    /// keyword list) — `correct_keyword`'s role ends upstream of this, at the
+    /// point the parser decided an identifier was worth tracking as a typo.
+    pub fn get_error(&self, input: &str, dialect: &dyn Dialect) -> ParseError {
         let inner = self.inner.borrow();
 
         match &*inner {
@@ -72,14 +79,18 @@ impl Backtrace {
                 message: "Unexpected error".to_string(),
                 line: 1,
                 column: 1,
+                span: None,
                 suggestion: None,
+                suggestions: Vec::new(),
                 context: None,
             },
             Some(inner) => {
-                let suggestion = inner
+                let suggestions = inner
                     .found
                     .as_ref()
-                    .and_then(|found| suggest_keyword(found));
+                    .map(|found| suggest_keywords(found, &inner.expected, dialect.keywords()))
+                    .unwrap_or_default();
+                let suggestion = suggestions.first().map(|(keyword, _)| keyword.clone());
 
                 let context = get_error_context(input, inner.furthest_pos);
 
@@ -94,11 +105,17 @@ impl Backtrace {
                     None => format!("Expected {}, reached end of input", expected_str),
                 };
 
+                // Zero-width span anchored at the furthest error position; the
+                // byte offset plus resolved line/column let tooling point at it.
+                let location = Location::new(inner.furthest_pos, inner.line, inner.column);
+
                 ParseError {
                     message,
                     line: inner.line,
                     column: inner.column,
+                    span: Some(Box::new(Span::new(location, location))),
                     suggestion,
+                    suggestions,
                     context,
                 }
             }
@@ -112,7 +129,15 @@ pub struct ParseError {
     pub message: String,
     pub line: usize,
     pub column: usize,
+    /// Source span of the error, when one is known, carrying byte offsets and
+    /// line/column positions. Errors built with [`ParseError::at_span`] cover
+    /// the offending token's range; errors from `Backtrace::get_error` and
+    /// recursion-limit errors carry a zero-width span at the error position.
+    pub span: Option<Box<Span>>,
     pub suggestion: Option<String>,
+    /// All candidate keywords ranked by similarity (best first), so an editor
+    /// can present a quick-fix menu. `suggestion` is just the top entry.
+    pub suggestions: Vec<(String, f64)>,
     pub context: Option<String>,
 }
 
@@ -140,46 +165,229 @@ impl fmt::Display for ParseError {
     }
 }
 
+/// Message carried by a recursion-limit error; kept as a constant so the kind
+/// can be recognised via [`ParseError::is_recursion_limit_exceeded`] rather
+/// than by matching a string literal.
+pub(crate) const RECURSION_LIMIT_MESSAGE: &str = "Recursion limit exceeded";
+
+impl ParseError {
+    /// Build a `RecursionLimitExceeded` error pointing at `pos` in `input`.
+    ///
+    /// Raised when the parser descends past its configured recursion limit,
+    /// so pathological input (deeply nested parentheses, long UNION chains)
+    /// surfaces a clean error instead of overflowing the stack.
+    pub fn recursion_limit_exceeded(input: &str, pos: usize) -> Self {
+        let (line, column) = position_to_line_col(input, pos);
+        let location = Location::new(pos, line, column);
+        ParseError {
+            message: RECURSION_LIMIT_MESSAGE.to_string(),
+            line,
+            column,
+            span: Some(Box::new(Span::new(location, location))),
+            suggestion: None,
+            suggestions: Vec::new(),
+            context: get_error_context(input, pos),
+        }
+    }
+
+    /// Whether this error is the dedicated recursion-limit kind, carrying the
+    /// `line`/`column` where the parser stopped descending.
+    pub fn is_recursion_limit_exceeded(&self) -> bool {
+        self.message == RECURSION_LIMIT_MESSAGE
+    }
+}
+
+impl ParseError {
+    /// Build an error anchored at a known [`Span`], used by the error-recovering
+    /// parser which carves diagnostics directly from the offending token rather
+    /// than from the furthest-error `Backtrace`.
+    pub fn at_span(input: &str, span: Span, message: impl Into<String>) -> Self {
+        ParseError {
+            message: message.into(),
+            line: span.start.line,
+            column: span.start.column,
+            span: Some(Box::new(span)),
+            suggestion: None,
+            suggestions: Vec::new(),
+            context: get_error_context(input, span.start.offset),
+        }
+    }
+}
+
 impl std::error::Error for ParseError {}
 
-/// Suggest similar keywords using Jaro-Winkler distance
-fn suggest_keyword(input: &str) -> Option<String> {
-    const KEYWORDS: &[&str] = &[
-        "SELECT",
-        "FROM",
-        "WHERE",
-        "WITH",
-        "RECURSIVE",
-        "INSERT",
-        "UPDATE",
-        "DELETE",
-        "UNION",
-        "ALL",
-        "AND",
-        "OR",
-        "AS",
-        "JOIN",
-        "LEFT",
-        "RIGHT",
-        "INNER",
-        "OUTER",
-        "ON",
-        "GROUP",
-        "ORDER",
-        "BY",
-        "HAVING",
-        "LIMIT",
-        "OFFSET",
-    ];
-
-    let input_upper = input.to_uppercase();
-
-    KEYWORDS
+/// Rank candidate keywords for `found` by Jaro-Winkler similarity, best first.
+///
+/// The keywords actually `expected` at the failure point are tried first, so a
+/// position where only `FROM` or `,` are legal never suggests an unrelated
+/// keyword. Only when the expected set is empty or yields nothing above
+/// threshold do we fall back to ranking against the full `keywords` set (the
+/// active dialect's reserved words). A candidate is kept only when its
+/// similarity is `>= 0.8`, Jaro-Winkler's usual rule-of-thumb cutoff for "close
+/// enough to suggest". The full ranked list is returned, genuine similarity
+/// scores and all, so editors can offer a quick-fix menu rather than a single
+/// guess.
+fn suggest_keywords(found: &str, expected: &[String], keywords: &[&str]) -> Vec<(String, f64)> {
+    let input_upper = found.to_uppercase();
+
+    let rank = |candidates: &[&str]| -> Vec<(String, f64)> {
+        let mut scored: Vec<(String, f64)> = candidates
+            .iter()
+            .map(|&c| (c.to_string(), jaro_winkler(&input_upper, &c.to_uppercase())))
+            .filter(|(_, score)| *score >= SIMILARITY_THRESHOLD)
+            .collect();
+        scored.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+        scored
+    };
+
+    let expected_refs: Vec<&str> = expected.iter().map(String::as_str).collect();
+    let from_expected = rank(&expected_refs);
+    if !from_expected.is_empty() {
+        return from_expected;
+    }
+    rank(keywords)
+}
+
+/// The similarity cutoff shared by [`suggest_keywords`] and [`correct_keyword`]
+/// so the two stages of keyword-typo handling never disagree about what counts
+/// as "close enough". Jaro-Winkler's usual rule-of-thumb for "close enough to
+/// suggest".
+const SIMILARITY_THRESHOLD: f64 = 0.8;
+
+/// Jaro similarity between two strings, in `[0, 1]`.
+///
+/// Counts characters that match within a sliding window of
+/// `max(len_a, len_b) / 2 - 1` positions, then penalizes matched characters
+/// that are out of order (transpositions).
+fn jaro(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let match_distance = a.len().max(b.len()) / 2;
+    let match_distance = match_distance.saturating_sub(1);
+
+    let mut a_matches = vec![false; a.len()];
+    let mut b_matches = vec![false; b.len()];
+    let mut matches = 0usize;
+
+    for (i, &ac) in a.iter().enumerate() {
+        let start = i.saturating_sub(match_distance);
+        let end = (i + match_distance + 1).min(b.len());
+        for (j, &bc) in b.iter().enumerate().take(end).skip(start) {
+            if b_matches[j] || bc != ac {
+                continue;
+            }
+            a_matches[i] = true;
+            b_matches[j] = true;
+            matches += 1;
+            break;
+        }
+    }
+
+    if matches == 0 {
+        return 0.0;
+    }
+
+    let mut transpositions = 0usize;
+    let mut k = 0;
+    for (i, &is_match) in a_matches.iter().enumerate() {
+        if !is_match {
+            continue;
+        }
+        while !b_matches[k] {
+            k += 1;
+        }
+        if a[i] != b[k] {
+            transpositions += 1;
+        }
+        k += 1;
+    }
+    let transpositions = transpositions / 2;
+
+    let m = matches as f64;
+    (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0
+}
+
+/// Jaro-Winkler similarity between two strings, in `[0, 1]`.
+///
+/// Boosts the Jaro score for strings sharing a common prefix (up to 4
+/// characters), scaled by the standard `0.1` factor, since keyword typos
+/// overwhelmingly preserve the first few letters (`SELCT`, `FORM`, `WHEER`).
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let jaro_sim = jaro(a, b);
+
+    let prefix_len = a
+        .chars()
+        .zip(b.chars())
+        .take(4)
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    jaro_sim + (prefix_len as f64 * 0.1 * (1.0 - jaro_sim))
+}
+
+/// Levenshtein edit distance between two strings.
+///
+/// Standard two-row dynamic-programming recurrence: `prev[j]` holds the
+/// distances for the previous source prefix; for each source char we compute
+/// `min(delete + 1, insert + 1, substitute + (a != b))`. O(m·n) time,
+/// O(n) space in the length of `b` — trivially fast for short keywords.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.is_empty() {
+        return b.len();
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0usize; b.len() + 1];
+
+    for (i, &ac) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, &bc) in b.iter().enumerate() {
+            let cost = if ac == bc { 0 } else { 1 };
+            curr[j + 1] = (prev[j + 1] + 1).min(curr[j] + 1).min(prev[j] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
+/// Closest keyword to `found` (case-insensitive) among `candidates`, by the
+/// same Jaro-Winkler metric [`suggest_keywords`] ranks with.
+///
+/// This only decides whether the parser should track `found` as a typo of one
+/// of the keywords legal at the current position (see
+/// [`Parser::check_keyword_typo`](crate::parser::Parser) — it feeds the
+/// `expected` set that `Backtrace::get_error` later hands to
+/// `suggest_keywords`. Sharing the metric and the threshold here means this
+/// gate and the ranking that ultimately fills in `ParseError::suggestion`
+/// never disagree about which keyword, if any, `found` is close enough to.
+pub fn correct_keyword(found: &str, candidates: &[&str]) -> Option<String> {
+    let upper = found.to_uppercase();
+    candidates
         .iter()
-        .map(|&keyword| (keyword, jaro_winkler(&input_upper, keyword)))
-        .filter(|(_, score)| *score > 0.8)
-        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
-        .map(|(keyword, _)| keyword.to_string())
+        .map(|&candidate| (candidate, jaro_winkler(&upper, &candidate.to_uppercase())))
+        .filter(|(_, score)| *score >= SIMILARITY_THRESHOLD)
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(candidate, _)| candidate.to_string())
+}
+
+/// Convert a byte offset in `input` to a 1-based `(line, column)` pair.
+///
+/// Shared with the AST so a node's span can be resolved to a source position
+/// using the same logic the error machinery uses.
+pub fn offset_to_line_col(input: &str, pos: usize) -> (usize, usize) {
+    position_to_line_col(input, pos)
 }
 
 /// Convert byte position to line and column
@@ -247,7 +455,7 @@ pub fn demonstrate_error_tracking() {
     println!("  Position 0: Try WITH... failed");
     backtrace1.track_error(0, "WITH", Some("SELCT"), input1);
 
-    let error1 = backtrace1.get_error(input1);
+    let error1 = backtrace1.get_error(input1, &crate::dialect::GenericDialect);
     println!("\n{} {}", "Result:".green(), error1.message);
     if let Some(suggestion) = error1.suggestion {
         println!("  {} {}", "Suggestion:".cyan(), suggestion);
@@ -276,7 +484,7 @@ pub fn demonstrate_error_tracking() {
     println!("  Backtrack to start, try INSERT INTO... failed");
     backtrace2.track_error(0, "INSERT", Some("SELECT"), input2);
 
-    let error2 = backtrace2.get_error(input2);
+    let error2 = backtrace2.get_error(input2, &crate::dialect::GenericDialect);
     println!("\n{} {}", "Result:".green(), error2.message);
     if let Some(suggestion) = error2.suggestion {
         println!(
@@ -314,7 +522,7 @@ pub fn demonstrate_error_tracking() {
     );
     backtrace3.track_error(22, "WHERE", Some("WHEER"), input3);
 
-    let error3 = backtrace3.get_error(input3);
+    let error3 = backtrace3.get_error(input3, &crate::dialect::GenericDialect);
     println!("\n{} {}", "Result:".green(), error3.message);
     if let Some(suggestion) = error3.suggestion {
         println!(
@@ -344,7 +552,7 @@ mod tests {
         backtrace.track_error(9, "FROM", Some("FORM"), input);
         backtrace.track_error(0, "INSERT", Some("SELECT"), input);
 
-        let error = backtrace.get_error(input);
+        let error = backtrace.get_error(input, &crate::dialect::GenericDialect);
 
         // Should report the furthest error (position 9)
         assert!(error.message.contains("FROM"));
@@ -362,7 +570,7 @@ mod tests {
         backtrace.track_error(8, "FROM", Some("FORM"), input);
         backtrace.track_error(19, "WHERE", Some("WHEER"), input);
 
-        let error = backtrace.get_error(input);
+        let error = backtrace.get_error(input, &crate::dialect::GenericDialect);
 
         // Should report the furthest error (position 19 - WHERE)
         assert!(error.message.contains("WHERE"));
@@ -373,10 +581,50 @@ mod tests {
     }
 
     #[test]
-    fn test_suggestion() {
-        assert_eq!(suggest_keyword("SELCT"), Some("SELECT".to_string()));
-        assert_eq!(suggest_keyword("FORM"), Some("FROM".to_string()));
-        assert_eq!(suggest_keyword("WHEER"), Some("WHERE".to_string()));
-        assert_eq!(suggest_keyword("xyz"), None);
+    fn test_levenshtein_distance() {
+        assert_eq!(levenshtein("WHERE", "WHERE"), 0);
+        assert_eq!(levenshtein("WHEER", "WHERE"), 2);
+        assert_eq!(levenshtein("FORM", "FROM"), 2);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_jaro_winkler_similarity() {
+        assert_eq!(jaro_winkler("WHERE", "WHERE"), 1.0);
+        assert!(jaro_winkler("SELCT", "SELECT") >= 0.8);
+        assert!(jaro_winkler("xyz", "SELECT") < 0.8);
+    }
+
+    #[test]
+    fn test_correct_keyword() {
+        assert_eq!(
+            correct_keyword("WHEER", &["FROM", "WHERE"]),
+            Some("WHERE".to_string())
+        );
+        assert_eq!(correct_keyword("users", &["FROM", "WHERE"]), None);
+    }
+
+    #[test]
+    fn test_suggestion_falls_back_to_global_keywords() {
+        // An empty expected set falls back to the global keyword list.
+        let best = |found: &str| {
+            suggest_keywords(found, &[], crate::dialect::GenericDialect.keywords())
+                .first()
+                .map(|(k, _)| k.clone())
+        };
+        assert_eq!(best("SELCT"), Some("SELECT".to_string()));
+        assert_eq!(best("FORM"), Some("FROM".to_string()));
+        assert_eq!(best("WHEER"), Some("WHERE".to_string()));
+        assert_eq!(best("xyz"), None);
+    }
+
+    #[test]
+    fn test_suggestion_prefers_expected_set() {
+        // At a position expecting only FROM or ',', a SELECT-ish typo must not
+        // be suggested — the expected set wins over the global list.
+        let expected = vec!["FROM".to_string(), ",".to_string()];
+        let ranked = suggest_keywords("FORM", &expected, crate::dialect::GenericDialect.keywords());
+        assert_eq!(ranked.first().map(|(k, _)| k.as_str()), Some("FROM"));
+        assert!(ranked.iter().all(|(k, _)| k != "SELECT"));
     }
 }
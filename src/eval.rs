@@ -0,0 +1,218 @@
+// Chapter 5: Evaluating Constant Expressions
+// Parsing produces a tree; this walks it and computes a value.
+
+use crate::expr::{BinaryOp, Expr, Literal, UnaryOp};
+use std::borrow::Cow;
+use std::fmt;
+
+/// A value produced by evaluating a constant expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<'a> {
+    Int(i64),
+    Float(f64),
+    Str(Cow<'a, str>),
+    Bool(bool),
+}
+
+impl Value<'_> {
+    /// SQL-style truthiness: non-zero numbers and non-empty strings are true.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Bool(b) => *b,
+            Value::Int(n) => *n != 0,
+            Value::Float(f) => *f != 0.0,
+            Value::Str(s) => !s.is_empty(),
+        }
+    }
+}
+
+/// Errors the evaluator must surface rather than guess at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// Division (or modulo) by zero.
+    DivideByZero,
+    /// Arithmetic applied to a non-numeric operand.
+    NonNumeric,
+    /// Operands that cannot be compared to one another.
+    TypeMismatch,
+    /// A column or `*` reference, which cannot be evaluated without a row.
+    NotConstant,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            EvalError::DivideByZero => "division by zero",
+            EvalError::NonNumeric => "non-numeric operand to arithmetic",
+            EvalError::TypeMismatch => "incomparable operands",
+            EvalError::NotConstant => "expression references a column and is not constant",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// Evaluate a constant expression (one with no column references) to a `Value`.
+pub fn eval<'a>(expr: &Expr<'a>) -> Result<Value<'a>, EvalError> {
+    match expr {
+        Expr::Literal(lit) => Ok(match lit {
+            Literal::Number(n) => Value::Int(*n),
+            Literal::Float(f) => Value::Float(*f),
+            Literal::String(s) => Value::Str(s.clone()),
+        }),
+        Expr::Paren(inner) => eval(inner),
+        Expr::Unary { op, operand } => eval_unary(*op, eval(operand)?),
+        Expr::Binary { left, op, right } => eval_binary(*op, eval(left)?, eval(right)?),
+        // Calls and membership tests depend on a row / runtime, never constant;
+        // `Error` is a parse-recovery placeholder with no value; `Cast` has no
+        // evaluator-level type coercion implemented yet.
+        Expr::Column(_)
+        | Expr::Star
+        | Expr::Call { .. }
+        | Expr::InList { .. }
+        | Expr::Between { .. }
+        | Expr::Cast { .. }
+        | Expr::Error => Err(EvalError::NotConstant),
+    }
+}
+
+fn eval_unary(op: UnaryOp, v: Value) -> Result<Value, EvalError> {
+    match op {
+        UnaryOp::Not => Ok(Value::Bool(!v.is_truthy())),
+        UnaryOp::Neg => match v {
+            Value::Int(n) => Ok(Value::Int(-n)),
+            Value::Float(f) => Ok(Value::Float(-f)),
+            _ => Err(EvalError::NonNumeric),
+        },
+    }
+}
+
+fn eval_binary<'a>(
+    op: BinaryOp,
+    left: Value<'a>,
+    right: Value<'a>,
+) -> Result<Value<'a>, EvalError> {
+    use BinaryOp::*;
+    match op {
+        And => Ok(Value::Bool(left.is_truthy() && right.is_truthy())),
+        Or => Ok(Value::Bool(left.is_truthy() || right.is_truthy())),
+        Plus | Minus | Multiply | Divide => arithmetic(op, left, right),
+        Equal | NotEqual | Less | Greater | LessEqual | GreaterEqual => {
+            compare(op, left, right)
+        }
+    }
+}
+
+/// Arithmetic with integer/float promotion.
+fn arithmetic<'a>(op: BinaryOp, left: Value<'a>, right: Value<'a>) -> Result<Value<'a>, EvalError> {
+    match (left, right) {
+        (Value::Int(a), Value::Int(b)) => int_arith(op, a, b),
+        (Value::Float(a), Value::Float(b)) => float_arith(op, a, b),
+        (Value::Int(a), Value::Float(b)) => float_arith(op, a as f64, b),
+        (Value::Float(a), Value::Int(b)) => float_arith(op, a, b as f64),
+        _ => Err(EvalError::NonNumeric),
+    }
+}
+
+fn int_arith<'a>(op: BinaryOp, a: i64, b: i64) -> Result<Value<'a>, EvalError> {
+    Ok(Value::Int(match op {
+        BinaryOp::Plus => a + b,
+        BinaryOp::Minus => a - b,
+        BinaryOp::Multiply => a * b,
+        BinaryOp::Divide => {
+            if b == 0 {
+                return Err(EvalError::DivideByZero);
+            }
+            a / b
+        }
+        _ => unreachable!("non-arithmetic operator routed to int_arith"),
+    }))
+}
+
+fn float_arith<'a>(op: BinaryOp, a: f64, b: f64) -> Result<Value<'a>, EvalError> {
+    Ok(Value::Float(match op {
+        BinaryOp::Plus => a + b,
+        BinaryOp::Minus => a - b,
+        BinaryOp::Multiply => a * b,
+        BinaryOp::Divide => {
+            if b == 0.0 {
+                return Err(EvalError::DivideByZero);
+            }
+            a / b
+        }
+        _ => unreachable!("non-arithmetic operator routed to float_arith"),
+    }))
+}
+
+/// Comparisons, returning a boolean, with numeric promotion.
+fn compare<'a>(op: BinaryOp, left: Value<'a>, right: Value<'a>) -> Result<Value<'a>, EvalError> {
+    let ordering = match (&left, &right) {
+        (Value::Int(a), Value::Int(b)) => (*a as f64).partial_cmp(&(*b as f64)),
+        (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+        (Value::Int(a), Value::Float(b)) => (*a as f64).partial_cmp(b),
+        (Value::Float(a), Value::Int(b)) => a.partial_cmp(&(*b as f64)),
+        (Value::Str(a), Value::Str(b)) => a.partial_cmp(b),
+        (Value::Bool(a), Value::Bool(b)) => a.partial_cmp(b),
+        _ => return Err(EvalError::TypeMismatch),
+    };
+
+    let ordering = ordering.ok_or(EvalError::TypeMismatch)?;
+    use std::cmp::Ordering::*;
+    let result = match op {
+        BinaryOp::Equal => ordering == Equal,
+        BinaryOp::NotEqual => ordering != Equal,
+        BinaryOp::Less => ordering == Less,
+        BinaryOp::Greater => ordering == Greater,
+        BinaryOp::LessEqual => ordering != Greater,
+        BinaryOp::GreaterEqual => ordering != Less,
+        _ => unreachable!("non-comparison operator routed to compare"),
+    };
+    Ok(Value::Bool(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Backtrace;
+    use crate::parser::Parser;
+    use crate::token::tokenize;
+
+    // `Parser::new` unifies the tokens/backtrace/input lifetimes, so a helper
+    // that owns the tokens and backtrace can't return the `Value` it parsed —
+    // it would be returning a reference to its own locals. Assert inside the
+    // helper instead, while everything is still in scope.
+    fn eval_str(input: &str, expected: Result<Value, EvalError>) {
+        let tokens = tokenize(input);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, input);
+        let expr = parser.parse_expr().expect("should parse");
+        assert_eq!(eval(&expr), expected);
+    }
+
+    #[test]
+    fn test_arithmetic_precedence() {
+        eval_str("2 + 3 * 4", Ok(Value::Int(14)));
+    }
+
+    #[test]
+    fn test_int_float_promotion() {
+        eval_str("1 + 2.5", Ok(Value::Float(3.5)));
+    }
+
+    #[test]
+    fn test_comparison_and_logic() {
+        eval_str("2 > 1 AND 3 = 3", Ok(Value::Bool(true)));
+        eval_str("0 OR 0", Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn test_divide_by_zero() {
+        eval_str("1 / 0", Err(EvalError::DivideByZero));
+    }
+
+    #[test]
+    fn test_column_is_not_constant() {
+        eval_str("age + 1", Err(EvalError::NotConstant));
+    }
+}
@@ -4,12 +4,14 @@
 use crate::parser::{ParseResult, Parser};
 use crate::token::TokenKind;
 use colored::*;
+use std::borrow::Cow;
 
 /// Expression AST with zero-copy strings
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expr<'a> {
     /// Column reference
-    Column(&'a str),
+    Column(#[cfg_attr(feature = "serde", serde(borrow))] &'a str),
 
     /// Literal value
     Literal(Literal<'a>),
@@ -21,21 +23,66 @@ pub enum Expr<'a> {
         right: Box<Expr<'a>>,
     },
 
+    /// Unary (prefix) operation
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr<'a>>,
+    },
+
+    /// Function call, e.g. `COUNT(*)` or `lower(name)`
+    Call {
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        name: &'a str,
+        args: Vec<Expr<'a>>,
+        distinct: bool,
+    },
+
+    /// `expr IN (a, b, ...)` / `expr NOT IN (...)`
+    InList {
+        expr: Box<Expr<'a>>,
+        list: Vec<Expr<'a>>,
+        negated: bool,
+    },
+
+    /// `expr BETWEEN low AND high` / `expr NOT BETWEEN low AND high`
+    Between {
+        expr: Box<Expr<'a>>,
+        low: Box<Expr<'a>>,
+        high: Box<Expr<'a>>,
+        negated: bool,
+    },
+
     /// Parenthesized expression
     Paren(Box<Expr<'a>>),
 
+    /// PostgreSQL-style postfix cast, e.g. `age::text`. Only produced under
+    /// dialects where `Dialect::supports_cast_operator` is true.
+    Cast {
+        expr: Box<Expr<'a>>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        type_name: &'a str,
+    },
+
     /// Star (for SELECT *)
     Star,
+
+    /// Placeholder for an expression that failed to parse, inserted by the
+    /// error-recovering parser so the surrounding tree still builds.
+    Error,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Literal<'a> {
     Number(i64),
     Float(f64),
-    String(&'a str),
+    /// The decoded string value: `Cow::Borrowed` for the common escape-free
+    /// case (still zero-copy), `Cow::Owned` only when escapes were present.
+    String(#[cfg_attr(feature = "serde", serde(borrow))] Cow<'a, str>),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinaryOp {
     // Logical
     And,
@@ -56,6 +103,26 @@ pub enum BinaryOp {
     Divide,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnaryOp {
+    /// Logical negation (`NOT`)
+    Not,
+    /// Arithmetic negation (`-`)
+    Neg,
+}
+
+impl UnaryOp {
+    /// Convert a prefix token to a unary operator
+    pub fn from_token(token: TokenKind) -> Option<Self> {
+        match token {
+            TokenKind::Not => Some(UnaryOp::Not),
+            TokenKind::Minus => Some(UnaryOp::Neg),
+            _ => None,
+        }
+    }
+}
+
 impl BinaryOp {
     /// Convert token to binary operator
     pub fn from_token(token: TokenKind) -> Option<Self> {
@@ -75,8 +142,37 @@ impl BinaryOp {
             _ => None,
         }
     }
+
+    /// Binding power of this operator, matching the levels in `get_precedence`.
+    ///
+    /// Used by the canonical unparser to decide where parentheses are actually
+    /// required: a child binds looser than its parent exactly when its
+    /// precedence is lower.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Or => 10,
+            BinaryOp::And => 20,
+            BinaryOp::Equal | BinaryOp::NotEqual => 30,
+            BinaryOp::Less | BinaryOp::Greater | BinaryOp::LessEqual | BinaryOp::GreaterEqual => 40,
+            BinaryOp::Plus | BinaryOp::Minus => 50,
+            BinaryOp::Multiply | BinaryOp::Divide => 60,
+        }
+    }
 }
 
+/// Binding power of a prefix (unary) operator.
+///
+/// It sits above every binary precedence in `get_precedence` so that `NOT a = b`
+/// parses as `(NOT a) = b` and `-x + 1` as `(-x) + 1`.
+const PREFIX_BINDING_POWER: u8 = 70;
+
+/// Binding power of the mixfix `IN` / `BETWEEN` operators.
+///
+/// Placed between equality (30) and ordering (40) comparisons, and raised by
+/// one when parsing `BETWEEN`'s bounds so the separating `AND` — and any
+/// surrounding boolean `AND` — isn't swallowed into the upper bound.
+const IN_BETWEEN_BINDING_POWER: u8 = 35;
+
 /// Get operator precedence
 fn get_precedence(token: TokenKind) -> Option<(u8, bool)> {
     // Return (precedence, is_left_associative)
@@ -101,15 +197,79 @@ impl<'a> Parser<'a> {
 
     /// Parse expression with minimum precedence
     fn parse_expr_with_precedence(&mut self, min_prec: u8) -> ParseResult<Expr<'a>> {
+        let _guard = self.descend()?;
         let mut left = self.parse_primary()?;
 
+        // `::` is a postfix cast, binding tighter than any binary operator,
+        // so it's applied directly to `left` before the precedence loop below
+        // ever sees a binary operator. Gated on the dialect since only
+        // PostgreSQL recognizes it.
+        while self.dialect().supports_cast_operator()
+            && self.current().map(|t| t.kind) == Some(TokenKind::ColonColon)
+        {
+            self.advance(); // ::
+            let type_name = self.expect(TokenKind::Identifier)?.text;
+            left = Expr::Cast {
+                expr: Box::new(left),
+                type_name,
+            };
+        }
+
         while let Some(token) = self.current() {
-            if let Some((prec, is_left)) = get_precedence(token.kind) {
+            let kind = token.kind;
+
+            // Mixfix operators: `IN` and `BETWEEN`, optionally negated by `NOT`.
+            let negated = kind == TokenKind::Not;
+            let mix_kind = if negated {
+                self.peek_kind(1)
+            } else {
+                Some(kind)
+            };
+            match mix_kind {
+                Some(TokenKind::In) => {
+                    if IN_BETWEEN_BINDING_POWER < min_prec {
+                        break;
+                    }
+                    if negated {
+                        self.advance(); // NOT
+                    }
+                    self.advance(); // IN
+                    let list = self.parse_paren_expr_list()?;
+                    left = Expr::InList {
+                        expr: Box::new(left),
+                        list,
+                        negated,
+                    };
+                    continue;
+                }
+                Some(TokenKind::Between) => {
+                    if IN_BETWEEN_BINDING_POWER < min_prec {
+                        break;
+                    }
+                    if negated {
+                        self.advance(); // NOT
+                    }
+                    self.advance(); // BETWEEN
+                    let low = self.parse_expr_with_precedence(IN_BETWEEN_BINDING_POWER + 1)?;
+                    self.expect(TokenKind::And)?;
+                    let high = self.parse_expr_with_precedence(IN_BETWEEN_BINDING_POWER + 1)?;
+                    left = Expr::Between {
+                        expr: Box::new(left),
+                        low: Box::new(low),
+                        high: Box::new(high),
+                        negated,
+                    };
+                    continue;
+                }
+                _ => {}
+            }
+
+            if let Some((prec, is_left)) = get_precedence(kind) {
                 if prec < min_prec {
                     break;
                 }
 
-                let op_kind = token.kind;
+                let op_kind = kind;
                 self.advance();
                 let next_min_prec = if is_left { prec + 1 } else { prec };
                 let right = self.parse_expr_with_precedence(next_min_prec)?;
@@ -130,9 +290,25 @@ impl<'a> Parser<'a> {
     }
 
     /// Parse primary expression
+    ///
+    /// No `descend()` guard here: every recursive path out of this function
+    /// (a parenthesized group's `parse_expr`, a unary operand, a binary/mixfix
+    /// operand) re-enters through `parse_expr_with_precedence`, which already
+    /// guards recursion. Guarding here too would spend two depth units per
+    /// nesting level instead of one, halving the effective recursion limit.
     fn parse_primary(&mut self) -> ParseResult<Expr<'a>> {
         match self.current() {
             Some(token) => {
+                // Prefix operators bind tighter than any binary operator.
+                if let Some(op) = UnaryOp::from_token(token.kind) {
+                    self.advance();
+                    let operand = self.parse_expr_with_precedence(PREFIX_BINDING_POWER)?;
+                    return Ok(Expr::Unary {
+                        op,
+                        operand: Box::new(operand),
+                    });
+                }
+
                 match token.kind {
                     TokenKind::Number => {
                         let text = token.text;
@@ -151,16 +327,21 @@ impl<'a> Parser<'a> {
                         Ok(Expr::Literal(Literal::Float(f)))
                     }
                     TokenKind::String => {
-                        let text = token.text;
+                        // Decode quotes/escapes into the logical value (borrowed
+                        // when there's nothing to unescape).
+                        let value = token.value();
                         self.advance();
-                        // Remove quotes
-                        let s = &text[1..text.len() - 1];
-                        Ok(Expr::Literal(Literal::String(s)))
+                        Ok(Expr::Literal(Literal::String(value)))
                     }
-                    TokenKind::Identifier => {
+                    TokenKind::Identifier | TokenKind::QuotedIdentifier => {
                         let text = token.text;
                         self.advance();
-                        Ok(Expr::Column(text))
+                        // A following `(` turns a bare name into a function call.
+                        if self.current().map(|t| t.kind) == Some(TokenKind::LeftParen) {
+                            self.parse_call(text)
+                        } else {
+                            Ok(Expr::Column(text))
+                        }
                     }
                     TokenKind::Star => {
                         self.advance();
@@ -178,6 +359,39 @@ impl<'a> Parser<'a> {
             None => Err(self.error_at_current("Unexpected end of input")),
         }
     }
+
+    /// Parse the argument list of a function call, given its already-consumed name.
+    fn parse_call(&mut self, name: &'a str) -> ParseResult<Expr<'a>> {
+        self.expect(TokenKind::LeftParen)?;
+        let distinct = self.try_consume(TokenKind::Distinct);
+
+        let mut args = Vec::new();
+        if self.current().map(|t| t.kind) != Some(TokenKind::RightParen) {
+            args.push(self.parse_expr()?);
+            while self.try_consume(TokenKind::Comma) {
+                args.push(self.parse_expr()?);
+            }
+        }
+        self.expect(TokenKind::RightParen)?;
+
+        Ok(Expr::Call {
+            name,
+            args,
+            distinct,
+        })
+    }
+
+    /// Parse a parenthesized, comma-separated expression list (for `IN` and
+    /// `INSERT ... VALUES` rows).
+    pub(crate) fn parse_paren_expr_list(&mut self) -> ParseResult<Vec<Expr<'a>>> {
+        self.expect(TokenKind::LeftParen)?;
+        let mut list = vec![self.parse_expr()?];
+        while self.try_consume(TokenKind::Comma) {
+            list.push(self.parse_expr()?);
+        }
+        self.expect(TokenKind::RightParen)?;
+        Ok(list)
+    }
 }
 
 /// Demonstrate the Pratt parser
@@ -238,8 +452,55 @@ impl<'a> std::fmt::Display for Expr<'a> {
             Expr::Binary { left, op, right } => {
                 write!(f, "({} {} {})", left, op, right)
             }
+            Expr::Unary { op, operand } => write!(f, "({}{})", op, operand),
+            Expr::Call {
+                name,
+                args,
+                distinct,
+            } => {
+                write!(f, "{}(", name)?;
+                if *distinct {
+                    write!(f, "DISTINCT ")?;
+                }
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                write!(f, "({} {}IN (", expr, if *negated { "NOT " } else { "" })?;
+                for (i, item) in list.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "))")
+            }
+            Expr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => write!(
+                f,
+                "({} {}BETWEEN {} AND {})",
+                expr,
+                if *negated { "NOT " } else { "" },
+                low,
+                high
+            ),
             Expr::Paren(expr) => write!(f, "({})", expr),
+            Expr::Cast { expr, type_name } => write!(f, "{}::{}", expr, type_name),
             Expr::Star => write!(f, "*"),
+            Expr::Error => write!(f, "<error>"),
         }
     }
 }
@@ -274,6 +535,16 @@ impl std::fmt::Display for BinaryOp {
     }
 }
 
+impl std::fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            UnaryOp::Not => "NOT ",
+            UnaryOp::Neg => "-",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -306,4 +577,106 @@ mod tests {
         let expected = "(((age > 18) AND (status = 'active')) OR (admin = 1))";
         assert_eq!(format!("{}", expr), expected);
     }
+
+    #[test]
+    fn test_unary_not_binds_tighter_than_and() {
+        let input = "NOT a AND b";
+        let tokens = tokenize(input);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, input);
+
+        let expr = parser.parse_expr().unwrap();
+
+        // NOT binds tighter than AND: ((NOT a) AND b)
+        assert_eq!(format!("{}", expr), "((NOT a) AND b)");
+    }
+
+    #[test]
+    fn test_unary_neg() {
+        let input = "-x + 1";
+        let tokens = tokenize(input);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, input);
+
+        let expr = parser.parse_expr().unwrap();
+
+        // Negation binds tighter than addition: ((-x) + 1)
+        assert_eq!(format!("{}", expr), "((-x) + 1)");
+    }
+
+    #[test]
+    fn test_between_does_not_swallow_boolean_and() {
+        let input = "age BETWEEN 18 AND 65 AND active = 1";
+        let tokens = tokenize(input);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, input);
+
+        let expr = parser.parse_expr().unwrap();
+
+        // The trailing `AND active = 1` stays at the boolean level.
+        assert_eq!(
+            format!("{}", expr),
+            "((age BETWEEN 18 AND 65) AND (active = 1))"
+        );
+    }
+
+    #[test]
+    fn test_function_call_and_in_list() {
+        let input = "COUNT(*)";
+        let tokens = tokenize(input);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, input);
+        assert_eq!(format!("{}", parser.parse_expr().unwrap()), "COUNT(*)");
+
+        let input = "status IN ('a', 'b')";
+        let tokens = tokenize(input);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, input);
+        assert_eq!(
+            format!("{}", parser.parse_expr().unwrap()),
+            "(status IN ('a', 'b'))"
+        );
+    }
+
+    #[test]
+    fn test_postgres_cast_binds_tighter_than_unary() {
+        use crate::dialect::PostgreSqlDialect;
+        use crate::token::tokenize_with_dialect;
+
+        let input = "-age::int";
+        let tokens = tokenize_with_dialect(input, &PostgreSqlDialect);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::with_dialect(&tokens, &backtrace, input, &PostgreSqlDialect);
+
+        let expr = parser.parse_expr().unwrap();
+
+        // `::` binds tighter than unary minus: -(age::int)
+        assert_eq!(format!("{}", expr), "(-age::int)");
+    }
+
+    #[test]
+    fn test_cast_operator_is_postgres_only() {
+        let input = "age::int";
+        let tokens = tokenize(input);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, input);
+
+        // Under the default (generic) dialect, `::` isn't a cast, so parsing
+        // the expression stops at `age` and leaves `::int` unconsumed.
+        let expr = parser.parse_expr().unwrap();
+        assert_eq!(format!("{}", expr), "age");
+    }
+
+    #[test]
+    fn test_deep_nesting_errors_instead_of_panicking() {
+        // Thousands of nested parentheses must not overflow the stack.
+        let input = format!("{}1{}", "(".repeat(5000), ")".repeat(5000));
+        let tokens = tokenize(&input);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, &input);
+
+        let result = parser.parse_expr();
+        let err = result.expect_err("deep nesting should be rejected");
+        assert_eq!(err.message, "Recursion limit exceeded");
+    }
 }
@@ -1,15 +1,31 @@
 // Library exports for the SQL parser demo
 // This exposes the modules for testing and external use
 
+pub mod analyze;
 pub mod ast;
+pub mod dialect;
 pub mod error;
+pub mod eval;
 pub mod expr;
+pub mod owned;
 pub mod parser;
 pub mod token;
+pub mod validate;
+pub mod visitor;
 
 // Re-export commonly used types
-pub use ast::{Query, SelectStmt, Statement, With, CTE};
+pub use ast::{
+    DeleteStmt, InsertSource, InsertStmt, Location, Query, SelectStmt, Span, Spanned, Statement,
+    UpdateStmt, With, CTE,
+};
 pub use error::ParseError;
+pub use dialect::{AnsiDialect, Dialect, GenericDialect, MySqlDialect, PostgreSqlDialect};
 pub use expr::{BinaryOp, Expr, Literal};
-pub use parser::{parse_sql, parse_sql_to_string};
-pub use token::{Token, TokenKind};
+pub use owned::{OwnedExpr, OwnedLiteral, OwnedQuery, OwnedStatement};
+pub use parser::{
+    parse_sql, parse_sql_owned, parse_sql_recovering, parse_sql_to_string, parse_sql_with,
+    parse_sql_with_dialect, ParserOptions,
+};
+pub use token::{tokenize, tokenize_with, Token, TokenKind};
+pub use validate::{Rejection, Validator, Violation};
+pub use visitor::{ConstantFolder, ReferenceCollector, Visitor, VisitorMut};
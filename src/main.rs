@@ -1,14 +1,9 @@
 // How Rust's Type System Saved Our SQL Parser
 // Complete working demo from the blog post
 
-mod ast;
-mod error;
-mod expr;
-mod parser;
-mod token;
-
 use colored::*;
-use parser::{parse_sql, parse_sql_to_string};
+use sql_parser_demo::parser::{parse_sql, parse_sql_to_string};
+use sql_parser_demo::{ast, error, expr, token};
 
 fn main() {
     println!("{}", "=".repeat(60).bright_blue());
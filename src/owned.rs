@@ -0,0 +1,681 @@
+// Chapter 5: An owned AST for consumers that outlive the input buffer
+//
+// The borrowed `Statement<'a>` tree is zero-copy: every identifier and string
+// literal is a slice into the original SQL text, so the whole tree is tied to
+// that buffer's lifetime. That's ideal while parsing, but a caller that wants
+// to *store* a parse result — cache it, send it across a thread, re-emit it
+// later — can't keep the input alive forever. This module mirrors the tree with
+// owned `String`s, matching the note in `parser.rs` that production Databend
+// uses an owned-string approach.
+
+use crate::ast::{DeleteStmt, InsertSource, InsertStmt, Query, Statement, UpdateStmt, With, CTE};
+use crate::expr::{BinaryOp, Expr, Literal, UnaryOp};
+
+/// An owned, `'static` SQL statement detached from the source buffer.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnedStatement {
+    Query(OwnedQuery),
+    Insert(OwnedInsert),
+    Update(OwnedUpdate),
+    Delete(OwnedDelete),
+    Error,
+}
+
+/// Owned counterpart of [`Query`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnedQuery {
+    Select(Box<OwnedSelect>),
+    With {
+        with: OwnedWith,
+        query: Box<OwnedQuery>,
+    },
+    Union {
+        left: Box<OwnedQuery>,
+        all: bool,
+        right: Box<OwnedQuery>,
+    },
+}
+
+/// Owned counterpart of [`crate::ast::SelectStmt`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedSelect {
+    pub projection: Vec<OwnedExpr>,
+    pub from: Option<OwnedTableRef>,
+    pub where_clause: Option<OwnedExpr>,
+}
+
+/// Owned counterpart of [`crate::ast::TableRef`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedTableRef {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+/// Owned counterpart of [`With`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedWith {
+    pub recursive: bool,
+    pub ctes: Vec<OwnedCte>,
+}
+
+/// Owned counterpart of [`CTE`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedCte {
+    pub name: String,
+    pub columns: Option<Vec<String>>,
+    pub query: Box<OwnedQuery>,
+}
+
+/// Owned counterpart of [`InsertStmt`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedInsert {
+    pub table: String,
+    pub columns: Option<Vec<String>>,
+    pub source: OwnedInsertSource,
+}
+
+/// Owned counterpart of [`InsertSource`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnedInsertSource {
+    Values(Vec<Vec<OwnedExpr>>),
+    Query(Box<OwnedQuery>),
+}
+
+/// Owned counterpart of [`UpdateStmt`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedUpdate {
+    pub table: String,
+    pub assignments: Vec<(String, OwnedExpr)>,
+    pub where_clause: Option<OwnedExpr>,
+}
+
+/// Owned counterpart of [`DeleteStmt`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedDelete {
+    pub table: String,
+    pub where_clause: Option<OwnedExpr>,
+}
+
+/// Owned counterpart of [`Expr`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnedExpr {
+    Column(String),
+    Literal(OwnedLiteral),
+    Binary {
+        left: Box<OwnedExpr>,
+        op: BinaryOp,
+        right: Box<OwnedExpr>,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<OwnedExpr>,
+    },
+    Call {
+        name: String,
+        args: Vec<OwnedExpr>,
+        distinct: bool,
+    },
+    InList {
+        expr: Box<OwnedExpr>,
+        list: Vec<OwnedExpr>,
+        negated: bool,
+    },
+    Between {
+        expr: Box<OwnedExpr>,
+        low: Box<OwnedExpr>,
+        high: Box<OwnedExpr>,
+        negated: bool,
+    },
+    Paren(Box<OwnedExpr>),
+    Cast {
+        expr: Box<OwnedExpr>,
+        type_name: String,
+    },
+    Star,
+    Error,
+}
+
+/// Owned counterpart of [`Literal`]. Only the string arm actually borrows, so
+/// the numeric arms are copied verbatim.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnedLiteral {
+    Number(i64),
+    Float(f64),
+    String(String),
+}
+
+// The conversions below walk the borrowed tree once, copying each borrowed
+// `&str` into an owned `String`. Spans are intentionally dropped: they index
+// into the input buffer, which an owned tree is meant to outlive.
+
+impl<'a> From<&Statement<'a>> for OwnedStatement {
+    fn from(stmt: &Statement<'a>) -> Self {
+        match stmt {
+            Statement::Query(q) => OwnedStatement::Query(q.into()),
+            Statement::Insert(i) => OwnedStatement::Insert(i.into()),
+            Statement::Update(u) => OwnedStatement::Update(u.into()),
+            Statement::Delete(d) => OwnedStatement::Delete(d.into()),
+            Statement::Error => OwnedStatement::Error,
+        }
+    }
+}
+
+impl<'a> From<&Query<'a>> for OwnedQuery {
+    fn from(query: &Query<'a>) -> Self {
+        match query {
+            Query::Select(select) => OwnedQuery::Select(Box::new(OwnedSelect {
+                projection: select.projection.iter().map(OwnedExpr::from).collect(),
+                from: select.from.as_ref().map(OwnedTableRef::from),
+                where_clause: select.where_clause.as_ref().map(OwnedExpr::from),
+            })),
+            Query::With { with, query } => OwnedQuery::With {
+                with: with.into(),
+                query: Box::new(query.as_ref().into()),
+            },
+            Query::Union { left, all, right } => OwnedQuery::Union {
+                left: Box::new(left.as_ref().into()),
+                all: *all,
+                right: Box::new(right.as_ref().into()),
+            },
+        }
+    }
+}
+
+impl<'a> From<&crate::ast::TableRef<'a>> for OwnedTableRef {
+    fn from(table: &crate::ast::TableRef<'a>) -> Self {
+        OwnedTableRef {
+            name: table.name.to_string(),
+            alias: table.alias.map(str::to_string),
+        }
+    }
+}
+
+impl<'a> From<&With<'a>> for OwnedWith {
+    fn from(with: &With<'a>) -> Self {
+        OwnedWith {
+            recursive: with.recursive,
+            ctes: with.ctes.iter().map(OwnedCte::from).collect(),
+        }
+    }
+}
+
+impl<'a> From<&CTE<'a>> for OwnedCte {
+    fn from(cte: &CTE<'a>) -> Self {
+        OwnedCte {
+            name: cte.name.to_string(),
+            columns: cte
+                .columns
+                .as_ref()
+                .map(|cols| cols.iter().map(|c| c.to_string()).collect()),
+            query: Box::new(cte.query.as_ref().into()),
+        }
+    }
+}
+
+impl<'a> From<&InsertStmt<'a>> for OwnedInsert {
+    fn from(insert: &InsertStmt<'a>) -> Self {
+        OwnedInsert {
+            table: insert.table.to_string(),
+            columns: insert
+                .columns
+                .as_ref()
+                .map(|cols| cols.iter().map(|c| c.to_string()).collect()),
+            source: match &insert.source {
+                InsertSource::Values(rows) => OwnedInsertSource::Values(
+                    rows.iter()
+                        .map(|row| row.iter().map(OwnedExpr::from).collect())
+                        .collect(),
+                ),
+                InsertSource::Query(query) => {
+                    OwnedInsertSource::Query(Box::new(query.as_ref().into()))
+                }
+            },
+        }
+    }
+}
+
+impl<'a> From<&UpdateStmt<'a>> for OwnedUpdate {
+    fn from(update: &UpdateStmt<'a>) -> Self {
+        OwnedUpdate {
+            table: update.table.to_string(),
+            assignments: update
+                .assignments
+                .iter()
+                .map(|(col, expr)| (col.to_string(), OwnedExpr::from(expr)))
+                .collect(),
+            where_clause: update.where_clause.as_ref().map(OwnedExpr::from),
+        }
+    }
+}
+
+impl<'a> From<&DeleteStmt<'a>> for OwnedDelete {
+    fn from(delete: &DeleteStmt<'a>) -> Self {
+        OwnedDelete {
+            table: delete.table.to_string(),
+            where_clause: delete.where_clause.as_ref().map(OwnedExpr::from),
+        }
+    }
+}
+
+impl<'a> From<&Expr<'a>> for OwnedExpr {
+    fn from(expr: &Expr<'a>) -> Self {
+        match expr {
+            Expr::Column(name) => OwnedExpr::Column(name.to_string()),
+            Expr::Literal(lit) => OwnedExpr::Literal(lit.into()),
+            Expr::Binary { left, op, right } => OwnedExpr::Binary {
+                left: Box::new(left.as_ref().into()),
+                op: *op,
+                right: Box::new(right.as_ref().into()),
+            },
+            Expr::Unary { op, operand } => OwnedExpr::Unary {
+                op: *op,
+                operand: Box::new(operand.as_ref().into()),
+            },
+            Expr::Call {
+                name,
+                args,
+                distinct,
+            } => OwnedExpr::Call {
+                name: name.to_string(),
+                args: args.iter().map(OwnedExpr::from).collect(),
+                distinct: *distinct,
+            },
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => OwnedExpr::InList {
+                expr: Box::new(expr.as_ref().into()),
+                list: list.iter().map(OwnedExpr::from).collect(),
+                negated: *negated,
+            },
+            Expr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => OwnedExpr::Between {
+                expr: Box::new(expr.as_ref().into()),
+                low: Box::new(low.as_ref().into()),
+                high: Box::new(high.as_ref().into()),
+                negated: *negated,
+            },
+            Expr::Paren(inner) => OwnedExpr::Paren(Box::new(inner.as_ref().into())),
+            Expr::Cast { expr, type_name } => OwnedExpr::Cast {
+                expr: Box::new(expr.as_ref().into()),
+                type_name: type_name.to_string(),
+            },
+            Expr::Star => OwnedExpr::Star,
+            Expr::Error => OwnedExpr::Error,
+        }
+    }
+}
+
+impl<'a> From<&Literal<'a>> for OwnedLiteral {
+    fn from(lit: &Literal<'a>) -> Self {
+        match lit {
+            Literal::Number(n) => OwnedLiteral::Number(*n),
+            Literal::Float(f) => OwnedLiteral::Float(*f),
+            Literal::String(s) => OwnedLiteral::String(s.to_string()),
+        }
+    }
+}
+
+// Canonical unparsing: turn an owned tree back into SQL. Unlike the `Debug`
+// dump of `parse_sql_to_string`, this emits real SQL and parenthesises only
+// where operator precedence requires it, so `a + b * c` reprints verbatim while
+// `(a + b) * c` keeps its parentheses. Reparsing the output yields an equal
+// tree (see the round-trip property test), except where the input carried
+// parentheses that precedence makes redundant: those `Paren` nodes are not
+// reprinted, so the reparsed tree lacks them.
+
+/// Binding power above every binary operator, matching the parser's prefix
+/// power, so a unary operand lower than it is parenthesised.
+const PREFIX_PRECEDENCE: u8 = 70;
+
+/// Binding power of the mixfix `IN`/`BETWEEN` operators, matching the parser.
+const IN_BETWEEN_PRECEDENCE: u8 = 35;
+
+/// Binding power of the postfix `::` cast, matching the parser. Higher than
+/// `PREFIX_PRECEDENCE` so a cast operand always parenthesises anything looser
+/// (e.g. `-x::int` reprints as `(-x)::int`, not `-x::int` reparsing as `-(x::int)`).
+const CAST_PRECEDENCE: u8 = 80;
+
+impl OwnedStatement {
+    /// Render this statement as canonical SQL.
+    pub fn to_sql(&self) -> String {
+        match self {
+            OwnedStatement::Query(query) => query.to_sql(),
+            OwnedStatement::Insert(insert) => insert.to_sql(),
+            OwnedStatement::Update(update) => update.to_sql(),
+            OwnedStatement::Delete(delete) => delete.to_sql(),
+            OwnedStatement::Error => "<error>".to_string(),
+        }
+    }
+}
+
+impl OwnedQuery {
+    /// Render this query as canonical SQL.
+    pub fn to_sql(&self) -> String {
+        match self {
+            OwnedQuery::Select(select) => select.to_sql(),
+            OwnedQuery::With { with, query } => format!("{} {}", with.to_sql(), query.to_sql()),
+            OwnedQuery::Union { left, all, right } => format!(
+                "{} UNION{} {}",
+                left.to_sql(),
+                if *all { " ALL" } else { "" },
+                right.to_sql()
+            ),
+        }
+    }
+}
+
+impl OwnedSelect {
+    /// Render this SELECT as canonical SQL.
+    pub fn to_sql(&self) -> String {
+        let mut out = String::from("SELECT ");
+        for (i, expr) in self.projection.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            out.push_str(&expr.to_sql());
+        }
+        if let Some(from) = &self.from {
+            out.push_str(" FROM ");
+            out.push_str(&from.name);
+            if let Some(alias) = &from.alias {
+                out.push_str(" AS ");
+                out.push_str(alias);
+            }
+        }
+        if let Some(where_clause) = &self.where_clause {
+            out.push_str(" WHERE ");
+            out.push_str(&where_clause.to_sql());
+        }
+        out
+    }
+}
+
+impl OwnedWith {
+    fn to_sql(&self) -> String {
+        let mut out = String::from("WITH ");
+        if self.recursive {
+            out.push_str("RECURSIVE ");
+        }
+        for (i, cte) in self.ctes.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            out.push_str(&cte.name);
+            if let Some(columns) = &cte.columns {
+                out.push('(');
+                out.push_str(&columns.join(", "));
+                out.push(')');
+            }
+            out.push_str(" AS (");
+            out.push_str(&cte.query.to_sql());
+            out.push(')');
+        }
+        out
+    }
+}
+
+impl OwnedInsert {
+    fn to_sql(&self) -> String {
+        let mut out = format!("INSERT INTO {}", self.table);
+        if let Some(columns) = &self.columns {
+            out.push_str(&format!(" ({})", columns.join(", ")));
+        }
+        match &self.source {
+            OwnedInsertSource::Values(rows) => {
+                out.push_str(" VALUES ");
+                for (i, row) in rows.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    let cells: Vec<String> = row.iter().map(OwnedExpr::to_sql).collect();
+                    out.push_str(&format!("({})", cells.join(", ")));
+                }
+            }
+            OwnedInsertSource::Query(query) => {
+                out.push(' ');
+                out.push_str(&query.to_sql());
+            }
+        }
+        out
+    }
+}
+
+impl OwnedUpdate {
+    fn to_sql(&self) -> String {
+        let mut out = format!("UPDATE {} SET ", self.table);
+        for (i, (col, expr)) in self.assignments.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            out.push_str(&format!("{} = {}", col, expr.to_sql()));
+        }
+        if let Some(where_clause) = &self.where_clause {
+            out.push_str(" WHERE ");
+            out.push_str(&where_clause.to_sql());
+        }
+        out
+    }
+}
+
+impl OwnedDelete {
+    fn to_sql(&self) -> String {
+        let mut out = format!("DELETE FROM {}", self.table);
+        if let Some(where_clause) = &self.where_clause {
+            out.push_str(" WHERE ");
+            out.push_str(&where_clause.to_sql());
+        }
+        out
+    }
+}
+
+impl OwnedExpr {
+    /// Render this expression as canonical SQL, with minimal parentheses.
+    pub fn to_sql(&self) -> String {
+        let mut out = String::new();
+        self.write_sql(&mut out, 0);
+        out
+    }
+
+    /// Write this expression into `out`, wrapping it in parentheses only when
+    /// its binding power is looser than `parent_prec` requires.
+    fn write_sql(&self, out: &mut String, parent_prec: u8) {
+        match self {
+            OwnedExpr::Column(name) => out.push_str(name),
+            OwnedExpr::Literal(lit) => out.push_str(&lit.to_sql()),
+            OwnedExpr::Binary { left, op, right } => {
+                let prec = op.precedence();
+                let wrap = prec < parent_prec;
+                if wrap {
+                    out.push('(');
+                }
+                left.write_sql(out, prec);
+                out.push_str(&format!(" {} ", op));
+                // Left-associative: force the right child to parenthesise a
+                // same-precedence operator so meaning is preserved.
+                right.write_sql(out, prec + 1);
+                if wrap {
+                    out.push(')');
+                }
+            }
+            OwnedExpr::Unary { op, operand } => {
+                let wrap = PREFIX_PRECEDENCE < parent_prec;
+                if wrap {
+                    out.push('(');
+                }
+                out.push_str(&op.to_string());
+                operand.write_sql(out, PREFIX_PRECEDENCE);
+                if wrap {
+                    out.push(')');
+                }
+            }
+            OwnedExpr::Call {
+                name,
+                args,
+                distinct,
+            } => {
+                out.push_str(name);
+                out.push('(');
+                if *distinct {
+                    out.push_str("DISTINCT ");
+                }
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    out.push_str(&arg.to_sql());
+                }
+                out.push(')');
+            }
+            OwnedExpr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                let wrap = IN_BETWEEN_PRECEDENCE < parent_prec;
+                if wrap {
+                    out.push('(');
+                }
+                expr.write_sql(out, IN_BETWEEN_PRECEDENCE + 1);
+                out.push_str(if *negated { " NOT IN (" } else { " IN (" });
+                for (i, item) in list.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    out.push_str(&item.to_sql());
+                }
+                out.push(')');
+                if wrap {
+                    out.push(')');
+                }
+            }
+            OwnedExpr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let wrap = IN_BETWEEN_PRECEDENCE < parent_prec;
+                if wrap {
+                    out.push('(');
+                }
+                expr.write_sql(out, IN_BETWEEN_PRECEDENCE + 1);
+                out.push_str(if *negated { " NOT BETWEEN " } else { " BETWEEN " });
+                low.write_sql(out, IN_BETWEEN_PRECEDENCE + 1);
+                out.push_str(" AND ");
+                high.write_sql(out, IN_BETWEEN_PRECEDENCE + 1);
+                if wrap {
+                    out.push(')');
+                }
+            }
+            // Parentheses are regenerated from precedence, so the node is
+            // transparent here — canonical output carries no redundant pairs.
+            OwnedExpr::Paren(inner) => inner.write_sql(out, parent_prec),
+            OwnedExpr::Cast { expr, type_name } => {
+                // `::` is the tightest-binding operator (see `CAST_PRECEDENCE`),
+                // so the node itself never needs wrapping — only its operand can.
+                expr.write_sql(out, CAST_PRECEDENCE);
+                out.push_str("::");
+                out.push_str(type_name);
+            }
+            OwnedExpr::Star => out.push('*'),
+            OwnedExpr::Error => out.push_str("<error>"),
+        }
+    }
+}
+
+impl OwnedLiteral {
+    fn to_sql(&self) -> String {
+        match self {
+            OwnedLiteral::Number(n) => n.to_string(),
+            // Keep a fractional part so a whole-valued float reparses as a
+            // float, not an integer.
+            OwnedLiteral::Float(f) => {
+                let text = f.to_string();
+                if text.contains('.') {
+                    text
+                } else {
+                    format!("{}.0", text)
+                }
+            }
+            // Re-escape backslashes and quotes so the literal reparses to the
+            // same decoded value.
+            OwnedLiteral::String(s) => {
+                format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Backtrace;
+    use crate::parser::Parser;
+    use crate::token::tokenize;
+
+    fn owned(sql: &str) -> OwnedStatement {
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, sql);
+        let stmt = parser.parse_statement().expect("should parse");
+        OwnedStatement::from(&stmt)
+    }
+
+    #[test]
+    fn test_owned_statement_outlives_input() {
+        // The owned tree is independent of the (here, temporary) input buffer.
+        let stmt = owned(&String::from("SELECT a, b FROM t WHERE a = 1"));
+        match stmt {
+            OwnedStatement::Query(OwnedQuery::Select(select)) => {
+                assert_eq!(select.projection.len(), 2);
+                assert_eq!(select.from.unwrap().name, "t");
+            }
+            other => panic!("expected a SELECT, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_owned_insert_values() {
+        let stmt = owned("INSERT INTO t (id) VALUES (1)");
+        assert!(matches!(stmt, OwnedStatement::Insert(_)));
+    }
+
+    #[test]
+    fn test_to_sql_minimal_parens() {
+        // No spurious parentheses when precedence already disambiguates.
+        assert_eq!(
+            owned("SELECT a + b * c FROM t").to_sql(),
+            "SELECT a + b * c FROM t"
+        );
+        // Parentheses kept exactly where precedence requires them.
+        assert_eq!(
+            owned("SELECT (a + b) * c FROM t").to_sql(),
+            "SELECT (a + b) * c FROM t"
+        );
+    }
+
+    #[test]
+    fn test_to_sql_round_trips() {
+        let first = owned("SELECT a FROM t WHERE b = 1");
+        let second = owned(&first.to_sql());
+        assert_eq!(first, second);
+    }
+}
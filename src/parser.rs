@@ -1,28 +1,126 @@
 // The main parser module that combines all concepts
 
-use crate::ast::{Query, SelectStmt, Statement, TableRef, With, CTE};
-use crate::error::{Backtrace, ParseError};
+use crate::ast::{
+    DeleteStmt, InsertSource, InsertStmt, Location, Query, SelectStmt, Span, Statement, TableRef,
+    UpdateStmt, With, CTE,
+};
+use crate::dialect::{Dialect, GenericDialect};
+use crate::error::{correct_keyword, Backtrace, ParseError};
 use crate::expr::Expr;
+use crate::owned::OwnedStatement;
 use crate::token::{Token, TokenKind};
+use std::cell::Cell;
+use std::rc::Rc;
 
 pub type ParseResult<T> = Result<T, ParseError>;
 
+/// Default maximum recursion depth for the parser.
+pub const DEFAULT_RECURSION_LIMIT: usize = 128;
+
+/// The default dialect used when a caller doesn't specify one.
+static DEFAULT_DIALECT: GenericDialect = GenericDialect;
+
+/// Tunable parser behaviours, applied via [`Parser::with_options`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParserOptions {
+    /// Accept a trailing comma before `FROM`, `)` or the end of input, e.g.
+    /// `SELECT a, b, FROM t`. No other clause keyword ends a list this way.
+    pub trailing_commas: bool,
+    /// Disable the lenient typo-acceptance path in `parse_select`, so callers
+    /// who want fail-fast parsing get it.
+    pub strict_keywords: bool,
+}
+
 /// The parser structure with error tracking
 pub struct Parser<'a> {
     tokens: &'a [Token<'a>],
     pos: usize,
     backtrace: &'a Backtrace,
     input: &'a str, // Original input for error messages
+    dialect: &'a dyn Dialect,
+    options: ParserOptions,
+    // Budget shared with the RAII guard so siblings aren't penalised.
+    remaining_depth: Rc<Cell<usize>>,
+}
+
+/// RAII guard that restores the recursion budget when a production returns.
+///
+/// Dropping the guard re-increments the counter on *every* exit path —
+/// including the `?` early-returns — so sibling subtrees get the full budget.
+pub(crate) struct DepthGuard {
+    remaining: Rc<Cell<usize>>,
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        self.remaining.set(self.remaining.get() + 1);
+    }
 }
 
 impl<'a> Parser<'a> {
     pub fn new(tokens: &'a [Token<'a>], backtrace: &'a Backtrace, input: &'a str) -> Self {
+        Self::with_recursion_limit(tokens, backtrace, input, DEFAULT_RECURSION_LIMIT)
+    }
+
+    /// Create a parser with a custom recursion limit.
+    pub fn with_recursion_limit(
+        tokens: &'a [Token<'a>],
+        backtrace: &'a Backtrace,
+        input: &'a str,
+        recursion_limit: usize,
+    ) -> Self {
         Parser {
             tokens,
             pos: 0,
             backtrace,
             input,
+            dialect: &DEFAULT_DIALECT,
+            options: ParserOptions::default(),
+            remaining_depth: Rc::new(Cell::new(recursion_limit)),
+        }
+    }
+
+    /// Create a parser with tuned [`ParserOptions`].
+    pub fn with_options(
+        tokens: &'a [Token<'a>],
+        backtrace: &'a Backtrace,
+        input: &'a str,
+        options: ParserOptions,
+    ) -> Self {
+        let mut parser = Self::new(tokens, backtrace, input);
+        parser.options = options;
+        parser
+    }
+
+    /// Create a parser that follows the rules of a specific SQL dialect.
+    pub fn with_dialect(
+        tokens: &'a [Token<'a>],
+        backtrace: &'a Backtrace,
+        input: &'a str,
+        dialect: &'a dyn Dialect,
+    ) -> Self {
+        let mut parser = Self::new(tokens, backtrace, input);
+        parser.dialect = dialect;
+        parser
+    }
+
+    /// The dialect this parser is following.
+    pub fn dialect(&self) -> &'a dyn Dialect {
+        self.dialect
+    }
+
+    /// Enter a recursive production: decrement the depth budget, erroring if
+    /// it is exhausted, and return a guard that restores it on return.
+    pub(crate) fn descend(&self) -> ParseResult<DepthGuard> {
+        let remaining = self.remaining_depth.get();
+        if remaining == 0 {
+            let pos = self.current().map(|t| t.span.start.offset).unwrap_or(0);
+            return Err(ParseError::recursion_limit_exceeded(self.input, pos));
         }
+        self.remaining_depth.set(remaining - 1);
+        Ok(DepthGuard {
+            remaining: Rc::clone(&self.remaining_depth),
+        })
     }
 
     /// Current token
@@ -30,6 +128,66 @@ impl<'a> Parser<'a> {
         self.tokens.get(self.pos)
     }
 
+    /// Byte offset where the next token to be consumed begins.
+    ///
+    /// Used to mark the start of a production's span.
+    pub fn current_offset(&self) -> usize {
+        match self.current() {
+            Some(token) => token.span.start.offset,
+            None => self.prev_end(),
+        }
+    }
+
+    /// Byte offset just past the most recently consumed token.
+    ///
+    /// Used to seal the end of a production's span.
+    pub fn prev_end(&self) -> usize {
+        if self.pos > 0 && !self.tokens.is_empty() {
+            self.tokens[self.pos - 1].span.end.offset
+        } else {
+            0
+        }
+    }
+
+    /// Slice the original source for a span, e.g. to render underlined context.
+    pub fn source_slice(&self, span: Span) -> &'a str {
+        &self.input[span.byte_range()]
+    }
+
+    /// Location where the next token to be consumed begins.
+    ///
+    /// Used to mark the start of a production's span.
+    pub fn current_location(&self) -> Location {
+        match self.current() {
+            Some(token) => token.span.start,
+            None => self.prev_location(),
+        }
+    }
+
+    /// Location just past the most recently consumed token.
+    ///
+    /// Used to seal the end of a production's span.
+    pub fn prev_location(&self) -> Location {
+        if self.pos > 0 && !self.tokens.is_empty() {
+            self.tokens[self.pos - 1].span.end
+        } else {
+            Location::default()
+        }
+    }
+
+    /// Seal a span that began at `start` (from [`current_location`]) at the most
+    /// recently consumed token.
+    ///
+    /// [`current_location`]: Self::current_location
+    pub fn span_from(&self, start: Location) -> Span {
+        Span::new(start, self.prev_location())
+    }
+
+    /// Peek at the kind of the token `offset` positions ahead of the cursor.
+    pub fn peek_kind(&self, offset: usize) -> Option<TokenKind> {
+        self.tokens.get(self.pos + offset).map(|t| t.kind)
+    }
+
     /// Advance to next token
     pub fn advance(&mut self) -> &Token<'a> {
         let token = &self.tokens[self.pos];
@@ -45,22 +203,22 @@ impl<'a> Parser<'a> {
             Some(token) if token.kind == expected => Ok(self.advance()),
             Some(token) => {
                 self.backtrace.track_error(
-                    token.span.start,
+                    token.span.start.offset,
                     &format!("{:?}", expected),
                     Some(token.text),
                     self.input,
                 );
-                Err(self.backtrace.get_error(self.input))
+                Err(self.backtrace.get_error(self.input, self.dialect))
             }
             None => {
                 let pos = if self.pos > 0 && !self.tokens.is_empty() {
-                    self.tokens[self.pos - 1].span.end
+                    self.tokens[self.pos - 1].span.end.offset
                 } else {
                     0
                 };
                 self.backtrace
                     .track_error(pos, &format!("{:?}", expected), None, self.input);
-                Err(self.backtrace.get_error(self.input))
+                Err(self.backtrace.get_error(self.input, self.dialect))
             }
         }
     }
@@ -78,93 +236,56 @@ impl<'a> Parser<'a> {
     /// Parse identifier
     pub fn parse_identifier(&mut self) -> ParseResult<&'a str> {
         match self.current() {
-            Some(token) if token.kind == TokenKind::Identifier => Ok(self.advance().text),
+            Some(token)
+                if matches!(
+                    token.kind,
+                    TokenKind::Identifier | TokenKind::QuotedIdentifier
+                ) =>
+            {
+                Ok(self.advance().text)
+            }
             Some(token) => {
                 self.backtrace.track_error(
-                    token.span.start,
+                    token.span.start.offset,
                     "identifier",
                     Some(token.text),
                     self.input,
                 );
-                Err(self.backtrace.get_error(self.input))
+                Err(self.backtrace.get_error(self.input, self.dialect))
             }
             None => {
                 let pos = if self.pos > 0 && !self.tokens.is_empty() {
-                    self.tokens[self.pos - 1].span.end
+                    self.tokens[self.pos - 1].span.end.offset
                 } else {
                     0
                 };
                 self.backtrace
                     .track_error(pos, "identifier", None, self.input);
-                Err(self.backtrace.get_error(self.input))
+                Err(self.backtrace.get_error(self.input, self.dialect))
             }
         }
     }
 
     /// Create error at current position
     pub fn error_at_current(&self, msg: &str) -> ParseError {
-        let mut error = self.backtrace.get_error(self.input);
+        let mut error = self.backtrace.get_error(self.input, self.dialect);
         error.message = msg.to_string();
         error
     }
 
     /// Check if current token might be a typo for the expected keyword
-    fn check_for_keyword_typo(
-        &mut self,
-        expected_keyword: &str,
-        starts_with_chars: &[char],
-    ) -> bool {
-        if let Some(token) = self.current() {
-            if token.kind == TokenKind::Identifier {
-                let text_upper = token.text.to_uppercase();
-                for &ch in starts_with_chars {
-                    if text_upper.starts_with(ch) {
-                        self.backtrace.track_error(
-                            token.span.start,
-                            expected_keyword,
-                            Some(token.text),
-                            self.input,
-                        );
-                        return true;
-                    }
-                }
-            }
-        }
-        false
-    }
-
-    /// Check if current token might be a typo for WHERE keyword (with substring check)
-    fn check_for_where_typo(&mut self) -> bool {
-        if let Some(token) = self.current() {
-            if token.kind == TokenKind::Identifier {
-                let text_upper = token.text.to_uppercase();
-                if text_upper.starts_with('W') || text_upper.contains("HER") {
-                    self.backtrace.track_error(
-                        token.span.start,
-                        "WHERE",
-                        Some(token.text),
-                        self.input,
-                    );
-                    return true;
-                }
-            }
-        }
-        false
-    }
-
-    /// Check if current token is a specific WHERE typo pattern
-    fn check_for_specific_where_typos(&mut self) -> bool {
+    ///
+    /// If the current token is an identifier that `correct_keyword` finds close
+    /// to one of the `expected` keywords (by Jaro-Winkler similarity, not edit
+    /// distance), record it as a typo and return `true`. This replaces
+    /// the old bespoke prefix/substring checks with a single principled corrector.
+    fn check_keyword_typo(&mut self, expected: &[&str]) -> bool {
         if let Some(token) = self.current() {
             if token.kind == TokenKind::Identifier {
-                let text = token.text.to_uppercase();
-                if text.starts_with("WHEER")
-                    || text.starts_with("WHER")
-                    || text.starts_with("WHRE")
-                    || text == "WHEER"
-                {
+                if let Some(keyword) = correct_keyword(token.text, expected) {
                     self.backtrace.track_error(
-                        token.span.start,
-                        "WHERE",
+                        token.span.start.offset,
+                        &keyword,
                         Some(token.text),
                         self.input,
                     );
@@ -177,8 +298,18 @@ impl<'a> Parser<'a> {
 
     /// Parse a complete SQL statement  
     pub fn parse_statement(&mut self) -> ParseResult<Statement<'a>> {
+        let _guard = self.descend()?;
         let start_pos = self.pos;
 
+        // Data-manipulation statements begin with an unambiguous keyword, so
+        // dispatch on it directly instead of the lenient SELECT fallthrough.
+        match self.current().map(|t| t.kind) {
+            Some(TokenKind::Insert) => return self.parse_insert().map(Statement::Insert),
+            Some(TokenKind::Update) => return self.parse_update().map(Statement::Update),
+            Some(TokenKind::Delete) => return self.parse_delete().map(Statement::Delete),
+            _ => {}
+        }
+
         // Try WITH clause first
         if self.current().map(|t| t.kind) == Some(TokenKind::With) {
             match self.parse_with() {
@@ -190,12 +321,18 @@ impl<'a> Parser<'a> {
                                 query: Box::new(query),
                             }))
                         }
+                        // Hitting the recursion guard isn't "this wasn't a WITH
+                        // statement" — it's a real failure that must reach the
+                        // caller, not get discarded in favor of a generic
+                        // "expected INSERT/UPDATE/DELETE/WITH" error below.
+                        Err(e) if e.is_recursion_limit_exceeded() => return Err(e),
                         Err(_) => {
                             // Reset position and try other statement types
                             self.pos = start_pos;
                         }
                     }
                 }
+                Err(e) if e.is_recursion_limit_exceeded() => return Err(e),
                 Err(_) => {
                     // Reset position and try other statement types
                     self.pos = start_pos;
@@ -203,10 +340,18 @@ impl<'a> Parser<'a> {
             }
         }
 
-        // Try SELECT statement with lenient parsing for error tracking
+        // Try SELECT (and any UNION chain built on it) with lenient parsing
+        // for error tracking. Routed through parse_query, not parse_select
+        // directly, so a top-level `SELECT ... UNION SELECT ...` actually
+        // recurses through the UNION branch instead of stopping after the
+        // first SELECT.
         self.pos = start_pos;
-        match self.parse_select() {
-            Ok(stmt) => return Ok(Statement::Query(Query::Select(Box::new(stmt)))),
+        match self.parse_query() {
+            Ok(query) => return Ok(Statement::Query(query)),
+            // A recursion-limit error is the real error, not a cue to keep
+            // guessing at other statement kinds — propagate it rather than
+            // letting the generic fallback below mask it.
+            Err(e) if e.is_recursion_limit_exceeded() => return Err(e),
             Err(_) => {
                 // This was the furthest we could get
             }
@@ -217,21 +362,22 @@ impl<'a> Parser<'a> {
         if let Some(token) = self.current() {
             // Track errors for other statement types to show alternatives
             self.backtrace
-                .track_error(token.span.start, "INSERT", Some(token.text), self.input);
+                .track_error(token.span.start.offset, "INSERT", Some(token.text), self.input);
             self.backtrace
-                .track_error(token.span.start, "UPDATE", Some(token.text), self.input);
+                .track_error(token.span.start.offset, "UPDATE", Some(token.text), self.input);
             self.backtrace
-                .track_error(token.span.start, "DELETE", Some(token.text), self.input);
+                .track_error(token.span.start.offset, "DELETE", Some(token.text), self.input);
             self.backtrace
-                .track_error(token.span.start, "WITH", Some(token.text), self.input);
+                .track_error(token.span.start.offset, "WITH", Some(token.text), self.input);
         }
 
         // If all fail, return the furthest error
-        Err(self.backtrace.get_error(self.input))
+        Err(self.backtrace.get_error(self.input, self.dialect))
     }
 
     /// Parse SELECT statement with lenient keyword matching
     pub fn parse_select(&mut self) -> ParseResult<SelectStmt<'a>> {
+        let start = self.current_location();
         let mut had_errors = false;
 
         // Try to parse SELECT, but be lenient about typos
@@ -242,7 +388,7 @@ impl<'a> Parser<'a> {
             Some(token) if token.kind == TokenKind::Identifier => {
                 // Track this as an error
                 self.backtrace.track_error(
-                    token.span.start,
+                    token.span.start.offset,
                     "SELECT",
                     Some(token.text),
                     self.input,
@@ -251,30 +397,30 @@ impl<'a> Parser<'a> {
 
                 // Check if this looks like a SELECT typo
                 let text = token.text.to_uppercase();
-                if text.starts_with("SEL") && text.len() >= 4 {
+                if !self.options.strict_keywords && text.starts_with("SEL") && text.len() >= 4 {
                     // Could be a SELECT typo, continue to see how far we get
                     self.advance();
                 } else {
-                    return Err(self.backtrace.get_error(self.input));
+                    return Err(self.backtrace.get_error(self.input, self.dialect));
                 }
             }
             Some(token) => {
                 self.backtrace.track_error(
-                    token.span.start,
+                    token.span.start.offset,
                     "SELECT",
                     Some(token.text),
                     self.input,
                 );
-                return Err(self.backtrace.get_error(self.input));
+                return Err(self.backtrace.get_error(self.input, self.dialect));
             }
             None => {
                 let pos = if self.pos > 0 && !self.tokens.is_empty() {
-                    self.tokens[self.pos - 1].span.end
+                    self.tokens[self.pos - 1].span.end.offset
                 } else {
                     0
                 };
                 self.backtrace.track_error(pos, "SELECT", None, self.input);
-                return Err(self.backtrace.get_error(self.input));
+                return Err(self.backtrace.get_error(self.input, self.dialect));
             }
         }
 
@@ -290,8 +436,8 @@ impl<'a> Parser<'a> {
             Some(self.parse_table_ref()?)
         } else {
             // Check if there's an identifier that might be a misspelled FROM
-            if self.check_for_keyword_typo("FROM", &['F']) {
-                return Err(self.backtrace.get_error(self.input));
+            if self.check_keyword_typo(&["FROM"]) {
+                return Err(self.backtrace.get_error(self.input, self.dialect));
             }
             None
         };
@@ -301,36 +447,302 @@ impl<'a> Parser<'a> {
             Some(self.parse_expr()?)
         } else {
             // Check if there's an identifier that might be a misspelled WHERE
-            if self.check_for_where_typo() {
-                return Err(self.backtrace.get_error(self.input));
+            if self.check_keyword_typo(&["WHERE"]) {
+                return Err(self.backtrace.get_error(self.input, self.dialect));
             }
             None
         };
 
         // If we encountered errors during parsing, return the error
         if had_errors {
-            return Err(self.backtrace.get_error(self.input));
+            return Err(self.backtrace.get_error(self.input, self.dialect));
         }
 
         Ok(SelectStmt {
             projection,
             from,
             where_clause,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parse `INSERT INTO t (cols...) VALUES (...), (...)` or `INSERT ... SELECT`.
+    pub fn parse_insert(&mut self) -> ParseResult<InsertStmt<'a>> {
+        let _guard = self.descend()?;
+        let start = self.current_location();
+        self.expect(TokenKind::Insert)?;
+        self.expect(TokenKind::Into)?;
+        let table = self.parse_identifier()?;
+
+        // Optional parenthesised column list.
+        let columns = if self.current().map(|t| t.kind) == Some(TokenKind::LeftParen) {
+            self.advance();
+            let cols = self.parse_identifier_list()?;
+            self.expect(TokenKind::RightParen)?;
+            Some(cols)
+        } else {
+            None
+        };
+
+        let source = if self.try_consume(TokenKind::Values) {
+            let mut rows = vec![self.parse_values_row()?];
+            while self.try_consume(TokenKind::Comma) {
+                rows.push(self.parse_values_row()?);
+            }
+            InsertSource::Values(rows)
+        } else {
+            InsertSource::Query(Box::new(self.parse_query()?))
+        };
+
+        Ok(InsertStmt {
+            table,
+            columns,
+            source,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parse a single `VALUES` row. Dialects that support it (PostgreSQL) may
+    /// prefix the tuple with an explicit `ROW` constructor keyword; it is
+    /// otherwise just a parenthesised expression list.
+    fn parse_values_row(&mut self) -> ParseResult<Vec<Expr<'a>>> {
+        if self.dialect.supports_explicit_row() {
+            if let Some(token) = self.current() {
+                if token.kind == TokenKind::Identifier && token.text.eq_ignore_ascii_case("ROW") {
+                    self.advance();
+                }
+            }
+        }
+        self.parse_paren_expr_list()
+    }
+
+    /// Parse `UPDATE t SET col = expr, ... WHERE ...`.
+    pub fn parse_update(&mut self) -> ParseResult<UpdateStmt<'a>> {
+        let _guard = self.descend()?;
+        let start = self.current_location();
+        self.expect(TokenKind::Update)?;
+        let table = self.parse_identifier()?;
+        self.expect(TokenKind::Set)?;
+
+        let mut assignments = vec![self.parse_assignment()?];
+        while self.try_consume(TokenKind::Comma) {
+            assignments.push(self.parse_assignment()?);
+        }
+
+        let where_clause = if self.try_consume(TokenKind::Where) {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
+        Ok(UpdateStmt {
+            table,
+            assignments,
+            where_clause,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parse a single `col = expr` assignment within an `UPDATE ... SET`.
+    fn parse_assignment(&mut self) -> ParseResult<(&'a str, Expr<'a>)> {
+        let column = self.parse_identifier()?;
+        self.expect(TokenKind::Equal)?;
+        let value = self.parse_expr()?;
+        Ok((column, value))
+    }
+
+    /// Parse `DELETE FROM t WHERE ...`.
+    pub fn parse_delete(&mut self) -> ParseResult<DeleteStmt<'a>> {
+        let _guard = self.descend()?;
+        let start = self.current_location();
+        self.expect(TokenKind::Delete)?;
+        self.expect(TokenKind::From)?;
+        let table = self.parse_identifier()?;
+
+        let where_clause = if self.try_consume(TokenKind::Where) {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
+        Ok(DeleteStmt {
+            table,
+            where_clause,
+            span: self.span_from(start),
         })
     }
 
+    /// Parse a statement in error-recovering mode: instead of aborting at the
+    /// first failure, collect every diagnostic and return a partial AST with
+    /// [`Expr::Error`]/[`Statement::Error`] placeholders where parsing broke.
+    ///
+    /// Errors sharing a span are de-duplicated so the same failure isn't
+    /// reported twice by different productions.
+    pub fn parse_recovering(&mut self) -> (Option<Statement<'a>>, Vec<ParseError>) {
+        let mut errors = Vec::new();
+        let stmt = self.recover_statement(&mut errors);
+        dedupe_errors(&mut errors);
+        (stmt, errors)
+    }
+
+    fn recover_statement(&mut self, errors: &mut Vec<ParseError>) -> Option<Statement<'a>> {
+        match self.current().map(|t| t.kind) {
+            // DML keywords parse unambiguously; on failure, record and skip to a
+            // synchronization point, leaving a placeholder statement.
+            Some(TokenKind::Insert) | Some(TokenKind::Update) | Some(TokenKind::Delete) => {
+                match self.parse_statement() {
+                    Ok(stmt) => Some(stmt),
+                    Err(err) => {
+                        errors.push(err);
+                        self.synchronize();
+                        Some(Statement::Error)
+                    }
+                }
+            }
+            _ => {
+                let select = self.recover_select(errors);
+                Some(Statement::Query(Query::Select(Box::new(select))))
+            }
+        }
+    }
+
+    /// Lenient, clause-by-clause SELECT parse that records errors and keeps
+    /// going rather than bailing at the first one.
+    fn recover_select(&mut self, errors: &mut Vec<ParseError>) -> SelectStmt<'a> {
+        let start = self.current_location();
+
+        if !self.try_consume(TokenKind::Select) {
+            errors.push(self.error_current("SELECT"));
+        }
+
+        let projection = self.recover_expr_list(errors);
+
+        let from = if self.try_consume(TokenKind::From) {
+            match self.parse_table_ref() {
+                Ok(table) => Some(table),
+                Err(err) => {
+                    errors.push(err);
+                    self.synchronize();
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let where_clause = if self.try_consume(TokenKind::Where) {
+            match self.parse_expr() {
+                Ok(expr) => Some(expr),
+                Err(err) => {
+                    errors.push(err);
+                    self.synchronize();
+                    Some(Expr::Error)
+                }
+            }
+        } else {
+            None
+        };
+
+        SelectStmt {
+            projection,
+            from,
+            where_clause,
+            span: self.span_from(start),
+        }
+    }
+
+    /// Parse a comma-separated projection list, substituting [`Expr::Error`] for
+    /// any element that fails and resuming at the next comma or clause boundary.
+    fn recover_expr_list(&mut self, errors: &mut Vec<ParseError>) -> Vec<Expr<'a>> {
+        let mut exprs = Vec::new();
+        loop {
+            match self.parse_expr() {
+                Ok(expr) => exprs.push(expr),
+                Err(err) => {
+                    errors.push(err);
+                    self.synchronize();
+                    exprs.push(Expr::Error);
+                }
+            }
+
+            if !self.try_consume(TokenKind::Comma) {
+                break;
+            }
+        }
+        exprs
+    }
+
+    /// Panic-mode recovery: skip tokens until a synchronization point — a
+    /// statement boundary (`;`), a clause keyword (`FROM`/`WHERE`/`UNION`), a
+    /// list comma, or the `)` closing the current group. A paren-depth counter
+    /// keeps synchronization from escaping an enclosing subquery.
+    fn synchronize(&mut self) {
+        let mut depth: usize = 0;
+        while let Some(token) = self.current() {
+            match token.kind {
+                TokenKind::Eof => return,
+                TokenKind::LeftParen => {
+                    depth += 1;
+                    self.advance();
+                }
+                TokenKind::RightParen => {
+                    if depth == 0 {
+                        return;
+                    }
+                    depth -= 1;
+                    self.advance();
+                }
+                TokenKind::Semicolon if depth == 0 => {
+                    self.advance();
+                    return;
+                }
+                TokenKind::From | TokenKind::Where | TokenKind::Union | TokenKind::Comma
+                    if depth == 0 =>
+                {
+                    return;
+                }
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    /// Build a diagnostic anchored at the current token.
+    fn error_current(&self, expected: &str) -> ParseError {
+        match self.current() {
+            Some(token) => {
+                let message = if token.kind == TokenKind::Eof {
+                    format!("Expected {}, reached end of input", expected)
+                } else {
+                    format!("Expected {}, found '{}'", expected, token.text)
+                };
+                ParseError::at_span(self.input, token.span, message)
+            }
+            None => ParseError::at_span(
+                self.input,
+                Span::default(),
+                format!("Expected {}, reached end of input", expected),
+            ),
+        }
+    }
+
     /// Parse table reference
     fn parse_table_ref(&mut self) -> ParseResult<TableRef<'a>> {
+        let start = self.current_location();
         let name = self.parse_identifier()?;
 
         // Check for alias (but not common SQL keywords that are likely typos)
         let alias = if self.try_consume(TokenKind::As) {
             Some(self.parse_identifier()?)
         } else if let Some(token) = self.current() {
-            if token.kind == TokenKind::Identifier {
+            // A reserved keyword (per the active dialect) is never an alias.
+            if token.kind == TokenKind::Identifier
+                && !self.dialect.is_reserved_keyword(token.text)
+            {
                 // Check if this looks like a WHERE typo, not an alias
-                if self.check_for_specific_where_typos() {
-                    return Err(self.backtrace.get_error(self.input));
+                if self.check_keyword_typo(&["WHERE"]) {
+                    return Err(self.backtrace.get_error(self.input, self.dialect));
                 }
                 Some(self.parse_identifier()?)
             } else {
@@ -340,7 +752,11 @@ impl<'a> Parser<'a> {
             None
         };
 
-        Ok(TableRef { name, alias })
+        Ok(TableRef {
+            name,
+            alias,
+            span: self.span_from(start),
+        })
     }
 
     /// Parse comma-separated expression list
@@ -348,14 +764,31 @@ impl<'a> Parser<'a> {
         let mut exprs = vec![self.parse_expr()?];
 
         while self.try_consume(TokenKind::Comma) {
+            // A trailing comma before FROM / ) ends the list when allowed.
+            if self.options.trailing_commas && self.at_list_terminator() {
+                break;
+            }
             exprs.push(self.parse_expr()?);
         }
 
         Ok(exprs)
     }
 
+    /// Whether the cursor sits at a token that legitimately ends a list, so a
+    /// preceding comma can be treated as trailing.
+    fn at_list_terminator(&self) -> bool {
+        matches!(
+            self.current().map(|t| t.kind),
+            Some(TokenKind::From)
+                | Some(TokenKind::RightParen)
+                | Some(TokenKind::Eof)
+                | None
+        )
+    }
+
     /// Parse WITH clause (including CTEs)
     pub fn parse_with(&mut self) -> ParseResult<With<'a>> {
+        let _guard = self.descend()?;
         self.expect(TokenKind::With)?;
 
         // Check for RECURSIVE
@@ -373,6 +806,8 @@ impl<'a> Parser<'a> {
 
     /// Parse a single CTE
     fn parse_cte(&mut self) -> ParseResult<CTE<'a>> {
+        let _guard = self.descend()?;
+        let start = self.current_location();
         let name = self.parse_identifier()?;
 
         // Optional column list
@@ -394,6 +829,7 @@ impl<'a> Parser<'a> {
             name,
             columns,
             query,
+            span: self.span_from(start),
         })
     }
 
@@ -402,6 +838,9 @@ impl<'a> Parser<'a> {
         let mut idents = vec![self.parse_identifier()?];
 
         while self.try_consume(TokenKind::Comma) {
+            if self.options.trailing_commas && self.at_list_terminator() {
+                break;
+            }
             idents.push(self.parse_identifier()?);
         }
 
@@ -410,6 +849,7 @@ impl<'a> Parser<'a> {
 
     /// Parse a query (can be SELECT or WITH)
     pub fn parse_query(&mut self) -> ParseResult<Query<'a>> {
+        let _guard = self.descend()?;
         if self.current().map(|t| t.kind) == Some(TokenKind::With) {
             let with = self.parse_with()?;
             let query = Box::new(self.parse_query()?);
@@ -449,6 +889,78 @@ pub fn parse_sql(sql: &str) -> Result<(), ParseError> {
     Ok(())
 }
 
+/// Drop errors that share a span with an earlier one, preserving order.
+fn dedupe_errors(errors: &mut Vec<ParseError>) {
+    let mut seen: Vec<(usize, usize)> = Vec::new();
+    errors.retain(|err| {
+        // An error without a span shares no span with another, so it is kept.
+        let Some(key) = err.span.as_ref().map(|s| (s.start.offset, s.end.offset)) else {
+            return true;
+        };
+        if seen.contains(&key) {
+            false
+        } else {
+            seen.push(key);
+            true
+        }
+    });
+}
+
+/// Parse SQL in error-recovering mode, returning a (possibly partial) owned AST
+/// plus every diagnostic collected along the way.
+///
+/// Like [`parse_sql_owned`], the result is owned so it can outlive the input
+/// buffer. An `Ok` parse yields an empty error list.
+pub fn parse_sql_recovering(sql: &str) -> (Option<OwnedStatement>, Vec<ParseError>) {
+    use crate::token::tokenize;
+
+    let tokens = tokenize(sql);
+    let backtrace = Backtrace::new();
+    let mut parser = Parser::new(&tokens, &backtrace, sql);
+
+    let (stmt, errors) = parser.parse_recovering();
+    (stmt.as_ref().map(OwnedStatement::from), errors)
+}
+
+/// Parse SQL under a specific [`Dialect`], honouring its quoted-identifier and
+/// comment syntax. [`parse_sql`] is the [`GenericDialect`] shortcut.
+pub fn parse_sql_with_dialect(sql: &str, dialect: &dyn Dialect) -> Result<(), ParseError> {
+    use crate::token::tokenize_with_dialect;
+
+    let tokens = tokenize_with_dialect(sql, dialect);
+    let backtrace = Backtrace::new();
+    let mut parser = Parser::with_dialect(&tokens, &backtrace, sql, dialect);
+
+    let _stmt = parser.parse_statement()?;
+
+    Ok(())
+}
+
+/// Parse SQL under a specific [`Dialect`]; the short name for
+/// [`parse_sql_with_dialect`], paired with [`tokenize_with`](crate::token::tokenize_with).
+/// [`parse_sql`] is the [`GenericDialect`] shortcut.
+pub fn parse_sql_with(sql: &str, dialect: &dyn Dialect) -> Result<(), ParseError> {
+    parse_sql_with_dialect(sql, dialect)
+}
+
+/// Parse SQL into an owned, `'static` statement tree.
+///
+/// Unlike [`parse_sql`] (which validates and discards) and
+/// [`parse_sql_to_string`] (which only returns a Debug dump), this hands back a
+/// real [`OwnedStatement`] whose identifiers and literals are `String`s, so
+/// callers can store, transform, and re-emit it without keeping the input
+/// buffer alive — the owned-string approach production Databend takes.
+pub fn parse_sql_owned(sql: &str) -> Result<OwnedStatement, ParseError> {
+    use crate::token::tokenize;
+
+    let tokens = tokenize(sql);
+    let backtrace = Backtrace::new();
+    let mut parser = Parser::new(&tokens, &backtrace, sql);
+
+    let stmt = parser.parse_statement()?;
+    Ok(OwnedStatement::from(&stmt))
+}
+
 /// Parse SQL and return an owned representation (for testing)
 pub fn parse_sql_to_string(sql: &str) -> Result<String, ParseError> {
     use crate::token::tokenize;
@@ -460,3 +972,142 @@ pub fn parse_sql_to_string(sql: &str) -> Result<String, ParseError> {
     let stmt = parser.parse_statement()?;
     Ok(format!("{:?}", stmt))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token::tokenize;
+
+    #[test]
+    fn test_long_union_chain_hits_recursion_limit() {
+        // A UNION chain deeper than the limit must error, not overflow.
+        let mut sql = String::from("SELECT 1");
+        for _ in 0..300 {
+            sql.push_str(" UNION SELECT 1");
+        }
+        let tokens = tokenize(&sql);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, &sql);
+
+        let err = parser
+            .parse_statement()
+            .expect_err("deep UNION chain should be rejected");
+        assert_eq!(err.message, "Recursion limit exceeded");
+    }
+
+    #[test]
+    fn test_node_span_resolves_to_line_col() {
+        let sql = "SELECT * FROM users";
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, sql);
+        let stmt = parser.parse_statement().unwrap();
+
+        let span = stmt.span();
+        assert_eq!(parser.source_slice(span), "SELECT * FROM users");
+        let ((start_line, start_col), _) = span.line_col_range();
+        assert_eq!((start_line, start_col), (1, 1));
+    }
+
+    #[test]
+    fn test_trailing_comma_option() {
+        let sql = "SELECT a, b, FROM t";
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let options = ParserOptions {
+            trailing_commas: true,
+            ..ParserOptions::default()
+        };
+        let mut parser = Parser::with_options(&tokens, &backtrace, sql, options);
+        assert!(parser.parse_statement().is_ok());
+    }
+
+    #[test]
+    fn test_strict_keywords_rejects_typo() {
+        let sql = "SELCT a FROM t";
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let options = ParserOptions {
+            strict_keywords: true,
+            ..ParserOptions::default()
+        };
+        let mut parser = Parser::with_options(&tokens, &backtrace, sql, options);
+        assert!(parser.parse_statement().is_err());
+    }
+
+    #[test]
+    fn test_parse_insert_values() {
+        let sql = "INSERT INTO users (id, name) VALUES (1, 'a'), (2, 'b')";
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, sql);
+        let stmt = parser.parse_statement().unwrap();
+        assert!(matches!(stmt, Statement::Insert(_)));
+        assert_eq!(format!("{}", stmt), sql);
+    }
+
+    #[test]
+    fn test_parse_insert_select() {
+        let sql = "INSERT INTO t SELECT * FROM u";
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, sql);
+        let stmt = parser.parse_statement().unwrap();
+        assert!(matches!(stmt, Statement::Insert(_)));
+    }
+
+    #[test]
+    fn test_parse_update() {
+        let sql = "UPDATE users SET name = 'a', age = 30 WHERE id = 1";
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, sql);
+        let stmt = parser.parse_statement().unwrap();
+        assert!(matches!(stmt, Statement::Update(_)));
+        // `Expr::Binary`'s `Display` always parenthesizes, so the WHERE
+        // clause reprints as `(id = 1)`, not the unparenthesized input.
+        assert_eq!(
+            format!("{}", stmt),
+            "UPDATE users SET name = 'a', age = 30 WHERE (id = 1)"
+        );
+    }
+
+    #[test]
+    fn test_parse_delete() {
+        let sql = "DELETE FROM users WHERE id = 1";
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, sql);
+        let stmt = parser.parse_statement().unwrap();
+        assert!(matches!(stmt, Statement::Delete(_)));
+        // `Expr::Binary`'s `Display` always parenthesizes, so the WHERE
+        // clause reprints as `(id = 1)`, not the unparenthesized input.
+        assert_eq!(format!("{}", stmt), "DELETE FROM users WHERE (id = 1)");
+    }
+
+    #[test]
+    fn test_recovering_collects_multiple_errors() {
+        // Two broken projection elements should both be reported, and a partial
+        // AST should still come back.
+        let (stmt, errors) = parse_sql_recovering("SELECT +, / FROM t");
+        assert!(stmt.is_some());
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_recovering_clean_parse_has_no_errors() {
+        let (stmt, errors) = parse_sql_recovering("SELECT a, b FROM t");
+        assert!(stmt.is_some());
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_custom_recursion_limit_accepts_shallow_query() {
+        let sql = "SELECT 1 UNION SELECT 2 UNION SELECT 3";
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::with_recursion_limit(&tokens, &backtrace, sql, 16);
+
+        assert!(parser.parse_statement().is_ok());
+    }
+}
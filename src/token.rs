@@ -1,23 +1,123 @@
 // Chapter 1: Zero-Copy Tokenization with logos
 // This shows how Rust's lifetimes enable zero-copy parsing
 
+use crate::ast::{Location, Span};
+use crate::dialect::{Dialect, GenericDialect};
 use logos::Logos;
+use std::borrow::Cow;
 use std::fmt;
 use std::ops::Range;
 
 /// The token structure - notice the lifetime 'a
 /// This means the token doesn't own the string, just references it
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token<'a> {
     pub text: &'a str, // Zero-copy reference to original input!
     pub kind: TokenKind,
-    pub span: Range<usize>,
+    pub span: Span, // Byte offsets plus line/column, filled in by `tokenize`
 }
 
 impl<'a> Token<'a> {
-    pub fn new(text: &'a str, kind: TokenKind, span: Range<usize>) -> Self {
+    pub fn new(text: &'a str, kind: TokenKind, span: Span) -> Self {
         Token { text, kind, span }
     }
+
+    /// The half-open byte range this token covers, for zero-copy slicing.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.span.byte_range()
+    }
+
+    /// The logical value of a string or quoted-identifier token, with the
+    /// surrounding delimiters removed and escapes decoded.
+    ///
+    /// For `String`/`QuotedIdentifier` tokens this strips the opening and
+    /// closing quote, decodes backslash escapes (`\n`, `\t`, `\'`, `\\`, ...)
+    /// and doubled-quote escapes (`''`, ``` `` ```, `""`), and otherwise
+    /// returns the inner text untouched. The zero-copy invariant is preserved:
+    /// when nothing needs decoding the result borrows straight from the source
+    /// (`Cow::Borrowed`), and only an actual escape forces a `Cow::Owned`
+    /// allocation. `RawString` and `DollarString` tokens have their prefix or
+    /// `$tag$` delimiters stripped with nothing decoded, always borrowed. Any
+    /// other token kind is returned verbatim as borrowed.
+    pub fn value(&self) -> Cow<'a, str> {
+        // A raw/prefixed literal takes its body verbatim: strip the one-char
+        // prefix and the surrounding quotes, but decode nothing.
+        if self.kind == TokenKind::RawString {
+            let inner = self
+                .text
+                .get(1..)
+                .and_then(|s| s.strip_prefix(['\'', '"']))
+                .and_then(|s| s.strip_suffix(['\'', '"']));
+            return Cow::Borrowed(inner.unwrap_or(self.text));
+        }
+
+        // A dollar-quoted literal takes its body verbatim too: strip the
+        // `$tag$` opener (whose length we recover by finding the tag's
+        // closing `$`) and the matching closer, decoding nothing.
+        if self.kind == TokenKind::DollarString {
+            let inner = self.text[1..]
+                .find('$')
+                .map(|tag_end| tag_end + 2)
+                .and_then(|opener_len| {
+                    self.text
+                        .get(opener_len..self.text.len() - opener_len)
+                });
+            return Cow::Borrowed(inner.unwrap_or(self.text));
+        }
+
+        let quote = match self.kind {
+            TokenKind::String | TokenKind::QuotedIdentifier => {
+                self.text.chars().next().filter(|c| !c.is_alphanumeric())
+            }
+            _ => return Cow::Borrowed(self.text),
+        };
+        let Some(quote) = quote else {
+            return Cow::Borrowed(self.text);
+        };
+        // Strip the matching delimiters; leave malformed tokens untouched.
+        let inner = match self
+            .text
+            .strip_prefix(quote)
+            .and_then(|s| s.strip_suffix(quote))
+        {
+            Some(inner) => inner,
+            None => return Cow::Borrowed(self.text),
+        };
+
+        let doubled = {
+            let mut buf = [0u8; 4];
+            let d: &str = quote.encode_utf8(&mut buf);
+            inner.contains(&format!("{d}{d}"))
+        };
+        if !inner.contains('\\') && !doubled {
+            return Cow::Borrowed(inner);
+        }
+
+        let mut out = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('0') => out.push('\0'),
+                    Some(other) => out.push(other),
+                    None => out.push('\\'),
+                }
+            } else if c == quote {
+                // A doubled delimiter collapses to a single literal one.
+                if chars.clone().next() == Some(quote) {
+                    chars.next();
+                }
+                out.push(quote);
+            } else {
+                out.push(c);
+            }
+        }
+        Cow::Owned(out)
+    }
 }
 
 impl fmt::Display for Token<'_> {
@@ -28,6 +128,7 @@ impl fmt::Display for Token<'_> {
 
 /// Token types using logos for fast tokenization
 #[derive(Logos, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[logos(skip r"[ \t\r\n\f]+")] // Skip whitespace
 #[logos(skip r"--[^\n]*")] // Skip SQL comments
 pub enum TokenKind {
@@ -62,6 +163,18 @@ pub enum TokenKind {
     #[regex("(?i)OR")]
     Or,
 
+    #[regex("(?i)NOT")]
+    Not,
+
+    #[regex("(?i)IN")]
+    In,
+
+    #[regex("(?i)BETWEEN")]
+    Between,
+
+    #[regex("(?i)DISTINCT")]
+    Distinct,
+
     #[regex("(?i)INSERT")]
     Insert,
 
@@ -71,13 +184,39 @@ pub enum TokenKind {
     #[regex("(?i)DELETE")]
     Delete,
 
+    #[regex("(?i)INTO")]
+    Into,
+
+    #[regex("(?i)VALUES")]
+    Values,
+
+    #[regex("(?i)SET")]
+    Set,
+
     // Identifiers and literals
     #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*")]
     Identifier,
 
+    // A quoted identifier (e.g. MySQL `col`, ANSI "col"). Produced by the
+    // dialect-aware scanning pass in `tokenize_with_dialect`, not by logos, so
+    // it has no regex — like `Eof`.
+    QuotedIdentifier,
+
     #[regex(r"'([^'\\]|\\.)*'")]
     String,
 
+    // A prefixed / raw string literal (e.g. `R'abc'`, `R"abc"`, `E'...'`).
+    // Like `QuotedIdentifier`, it's carved out by the hand-written pass in
+    // `tokenize_with_dialect` rather than by logos, since its body is taken
+    // verbatim with no escape processing.
+    RawString,
+
+    // A PostgreSQL dollar-quoted string literal (`$$abc$$`, `$tag$abc$tag$`).
+    // Only produced under dialects where `Dialect::supports_dollar_quoted_strings`
+    // is true; like `RawString`, its body is taken verbatim with no escape
+    // processing.
+    DollarString,
+
     #[regex(r"-?[0-9]+")]
     Number,
 
@@ -116,6 +255,12 @@ pub enum TokenKind {
     #[token(">=")]
     GreaterEqual,
 
+    // PostgreSQL-style type cast, e.g. `age::text`. Tokenized unconditionally
+    // (it's just two colons); whether it's actually *parsed* as a cast is
+    // gated by `Dialect::supports_cast_operator`.
+    #[token("::")]
+    ColonColon,
+
     // Delimiters
     #[token("(")]
     LeftParen,
@@ -137,22 +282,248 @@ pub enum TokenKind {
 /// The key insight: we return tokens that reference the original input
 /// No string copying happens here!
 pub fn tokenize(input: &str) -> Vec<Token<'_>> {
-    let mut tokens = Vec::new();
-    let mut lexer = TokenKind::lexer(input);
+    tokenize_with_dialect(input, &GenericDialect)
+}
+
+/// Dialect-parametrized tokenizer entry point; the short name paired with
+/// [`parse_sql_with`](crate::parser::parse_sql_with).
+pub fn tokenize_with<'a>(input: &'a str, dialect: &dyn Dialect) -> Vec<Token<'a>> {
+    tokenize_with_dialect(input, dialect)
+}
+
+/// Tokenize `input` under a specific [`Dialect`], handling its quoted-identifier
+/// delimiter and line-comment styles.
+///
+/// Because logos is compile-time, the dialect-specific bits are handled by a
+/// thin hand-written pass: quoted runs and extra comments are carved out here
+/// (the former emitted as [`TokenKind::QuotedIdentifier`]), and everything in
+/// between is delegated to the logos lexer. The zero-copy invariant holds — every
+/// token's `text`/`span` still points at the exact source substring.
+pub fn tokenize_with_dialect<'a>(input: &'a str, dialect: &dyn Dialect) -> Vec<Token<'a>> {
+    let quote = dialect.identifier_quote();
+    let comments = dialect.line_comment_prefixes();
+
+    // First pass: collect (kind, byte-range) in source order.
+    let mut raw: Vec<(TokenKind, Range<usize>)> = Vec::new();
+    let bytes = input.as_bytes();
+    let len = input.len();
+    let mut i = 0;
+    let mut seg_start = 0;
+
+    while i < len {
+        // A prefixed / raw string literal: a single `R`/`E` prefix (any case)
+        // immediately followed by `'` or `"`, at a token boundary. The body is
+        // taken verbatim up to the closing quote — no escape processing — so
+        // the token's text/span still point at the exact source substring.
+        let first = input[i..].chars().next().unwrap();
+        if matches!(first, 'R' | 'r' | 'E' | 'e') && !prev_is_identifier_part(input, i, dialect) {
+            if let Some(q) = input[i + 1..].chars().next() {
+                if q == '\'' || q == '"' {
+                    flush_segment(input, seg_start..i, &mut raw);
+                    let mut j = i + 1 + q.len_utf8();
+                    while j < len {
+                        if input[j..].starts_with(q) {
+                            j += q.len_utf8();
+                            break;
+                        }
+                        j += input[j..].chars().next().unwrap().len_utf8();
+                    }
+                    raw.push((TokenKind::RawString, i..j));
+                    i = j;
+                    seg_start = i;
+                    continue;
+                }
+            }
+        }
+
+        // A dollar-quoted string literal (PostgreSQL): `$tag$...$tag$`, where
+        // `tag` is empty or an identifier-like run. Carved out wholesale like
+        // `RawString`, since its body is taken verbatim with no escape
+        // processing, and only under dialects that opt in.
+        if first == '$' && dialect.supports_dollar_quoted_strings() {
+            if let Some(tag_len) = dollar_tag_len(input, i) {
+                let opener_len = tag_len + 2; // leading `$` + tag + trailing `$`
+                let opener = &input[i..i + opener_len];
+                if let Some(rel) = input[i + opener_len..].find(opener) {
+                    let j = i + opener_len + rel + opener.len();
+                    flush_segment(input, seg_start..i, &mut raw);
+                    raw.push((TokenKind::DollarString, i..j));
+                    i = j;
+                    seg_start = i;
+                    continue;
+                }
+            }
+        }
+
+        // An ordinary single-quoted string literal. Carved out wholesale
+        // (rather than left for the R/E prefix check and logos to sort out a
+        // character at a time) so a body character that happens to equal
+        // `R`/`E` and sits right before the closing quote — e.g. the lone
+        // content of `'E'`, or the embedded `' R'` — can never be mistaken
+        // for the start of a fresh prefixed-string literal: the prefix check
+        // above only ever sees this string's opening quote, never a
+        // character from inside its body. Escapes (`\'`, `\\`, ...) are
+        // skipped verbatim, matching the logos `String` regex this delegates
+        // to for ordinary (non-prefixed) segments.
+        if first == '\'' {
+            flush_segment(input, seg_start..i, &mut raw);
+            let mut j = i + 1;
+            while j < len {
+                let c = input[j..].chars().next().unwrap();
+                if c == '\\' {
+                    j += c.len_utf8();
+                    if j < len {
+                        j += input[j..].chars().next().unwrap().len_utf8();
+                    }
+                    continue;
+                }
+                j += c.len_utf8();
+                if c == '\'' {
+                    break;
+                }
+            }
+            raw.push((TokenKind::String, i..j));
+            i = j;
+            seg_start = i;
+            continue;
+        }
+
+        // A quoted identifier: scan to its closing delimiter (doubled delimiter
+        // escapes itself) and emit a single QuotedIdentifier token.
+        if quote == Some(input[i..].chars().next().unwrap()) {
+            let q = quote.unwrap();
+            flush_segment(input, seg_start..i, &mut raw);
+            let mut j = i + q.len_utf8();
+            while j < len {
+                if input[j..].starts_with(q) {
+                    let after = j + q.len_utf8();
+                    if input[after..].starts_with(q) {
+                        j = after + q.len_utf8(); // doubled delimiter, keep scanning
+                        continue;
+                    }
+                    j = after;
+                    break;
+                }
+                j += input[j..].chars().next().unwrap().len_utf8();
+            }
+            raw.push((TokenKind::QuotedIdentifier, i..j));
+            i = j;
+            seg_start = i;
+            continue;
+        }
+
+        // A dialect line comment: skip to end of line (the newline is left for
+        // the surrounding segment's whitespace handling).
+        if let Some(prefix) = comments.iter().find(|p| input[i..].starts_with(**p)) {
+            flush_segment(input, seg_start..i, &mut raw);
+            let mut j = i + prefix.len();
+            while j < len && bytes[j] != b'\n' {
+                j += 1;
+            }
+            i = j;
+            seg_start = i;
+            continue;
+        }
+
+        i += input[i..].chars().next().unwrap().len_utf8();
+    }
+    flush_segment(input, seg_start..len, &mut raw);
+
+    // Second pass: resolve each range to a line/column span using one forward
+    // cursor, then build the zero-copy tokens.
+    let mut positions = PositionMap::new(input);
+    let mut tokens: Vec<Token<'a>> = raw
+        .into_iter()
+        .map(|(kind, range)| {
+            let span = Span::new(positions.locate(range.start), positions.locate(range.end));
+            Token::new(&input[range], kind, span)
+        })
+        .collect();
+
+    let end = positions.locate(len);
+    tokens.push(Token::new("", TokenKind::Eof, Span::new(end, end)));
+    tokens
+}
 
+/// If `input[i..]` opens a dollar-quote tag (`$`, then zero or more
+/// identifier-like characters, then a closing `$`), returns the byte length of
+/// the tag itself (excluding the two `$` delimiters). Returns `None` if no
+/// closing `$` is found before a character that can't be part of a tag.
+fn dollar_tag_len(input: &str, i: usize) -> Option<usize> {
+    let mut len = 0;
+    for c in input[i + 1..].chars() {
+        if c == '$' {
+            return Some(len);
+        }
+        if c.is_ascii_alphanumeric() || c == '_' {
+            len += c.len_utf8();
+        } else {
+            return None;
+        }
+    }
+    None
+}
+
+/// Whether the character immediately before `i` is an identifier part under
+/// `dialect`, which would make a `R`/`E` at `i` part of an identifier (e.g. the
+/// `R` in `myR`) rather than a string-literal prefix.
+fn prev_is_identifier_part(input: &str, i: usize, dialect: &dyn Dialect) -> bool {
+    input[..i]
+        .chars()
+        .next_back()
+        .is_some_and(|c| dialect.is_identifier_part(c))
+}
+
+/// Run the logos lexer over an ordinary (non-quoted, non-comment) segment and
+/// push its tokens with offsets shifted back into the original input.
+fn flush_segment(input: &str, segment: Range<usize>, raw: &mut Vec<(TokenKind, Range<usize>)>) {
+    if segment.start >= segment.end {
+        return;
+    }
+    let base = segment.start;
+    let mut lexer = TokenKind::lexer(&input[segment]);
     while let Some(result) = lexer.next() {
         if let Ok(kind) = result {
-            let span = lexer.span();
-            let text = &input[span.clone()];
-            tokens.push(Token::new(text, kind, span));
+            let s = lexer.span();
+            raw.push((kind, base + s.start..base + s.end));
         }
     }
+}
 
-    // Add EOF token
-    let len = input.len();
-    tokens.push(Token::new("", TokenKind::Eof, len..len));
+/// Incremental byte-offset → [`Location`] mapper.
+///
+/// Offsets are expected to be queried in non-decreasing order (tokens come out
+/// left to right), so a single advancing cursor keeps the whole pass O(n).
+struct PositionMap<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+    line: usize,
+    column: usize,
+}
 
-    tokens
+impl<'a> PositionMap<'a> {
+    fn new(input: &'a str) -> Self {
+        PositionMap {
+            bytes: input.as_bytes(),
+            cursor: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn locate(&mut self, offset: usize) -> Location {
+        while self.cursor < offset {
+            if self.bytes[self.cursor] == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            } else if self.bytes[self.cursor] & 0xC0 != 0x80 {
+                // Count one column per UTF-8 scalar, not per continuation byte.
+                self.column += 1;
+            }
+            self.cursor += 1;
+        }
+        Location::new(offset, self.line, self.column)
+    }
 }
 
 /// Demonstrate memory efficiency
@@ -215,4 +586,99 @@ mod tests {
         assert_eq!(tokens[1].text, "name");
         assert_eq!(tokens[2].kind, TokenKind::From);
     }
+
+    #[test]
+    fn test_mysql_backtick_identifier_and_hash_comment() {
+        use crate::dialect::MySqlDialect;
+
+        let sql = "SELECT `from` # trailing\nFROM t";
+        let tokens = tokenize_with_dialect(sql, &MySqlDialect);
+
+        let quoted = tokens
+            .iter()
+            .find(|t| t.kind == TokenKind::QuotedIdentifier)
+            .expect("backtick identifier should be one token");
+        assert_eq!(quoted.text, "`from`");
+        // The `#` comment is skipped, so the next real token is FROM.
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::From));
+    }
+
+    #[test]
+    fn test_ansi_double_quoted_identifier() {
+        use crate::dialect::AnsiDialect;
+
+        let sql = "SELECT \"col\" FROM t";
+        let tokens = tokenize_with_dialect(sql, &AnsiDialect);
+        assert!(tokens
+            .iter()
+            .any(|t| t.kind == TokenKind::QuotedIdentifier && t.text == "\"col\""));
+    }
+
+    #[test]
+    fn test_span_tracks_line_and_column() {
+        // `users` starts on the second line, after the newline.
+        let sql = "SELECT *\nFROM users";
+        let tokens = tokenize(sql);
+
+        let users = tokens.iter().find(|t| t.text == "users").unwrap();
+        assert_eq!(users.span.start.offset, sql.find("users").unwrap());
+        assert_eq!((users.span.start.line, users.span.start.column), (2, 6));
+        assert_eq!(users.span.end.column, 11);
+    }
+
+    #[test]
+    fn test_value_is_borrowed_without_escapes() {
+        let sql = "SELECT 'plain'";
+        let tokens = tokenize(sql);
+        let s = tokens.iter().find(|t| t.kind == TokenKind::String).unwrap();
+        assert!(matches!(s.value(), Cow::Borrowed("plain")));
+    }
+
+    #[test]
+    fn test_value_decodes_backslash_escapes() {
+        let sql = r"SELECT 'it\'s\tok'";
+        let tokens = tokenize(sql);
+        let s = tokens.iter().find(|t| t.kind == TokenKind::String).unwrap();
+        assert_eq!(s.value(), Cow::Owned::<str>("it's\tok".to_string()));
+    }
+
+    #[test]
+    fn test_dollar_quoted_string_requires_postgres_dialect() {
+        use crate::dialect::{GenericDialect, PostgreSqlDialect};
+
+        let sql = "SELECT $$it's fine$$";
+        let tokens = tokenize_with_dialect(sql, &PostgreSqlDialect);
+        let s = tokens
+            .iter()
+            .find(|t| t.kind == TokenKind::DollarString)
+            .expect("dollar-quoted string should be one token under Postgres");
+        assert_eq!(s.text, "$$it's fine$$");
+        assert_eq!(s.value(), "it's fine");
+
+        // Without the dialect flag, `$` isn't special, so no DollarString is
+        // produced: `$$it` goes to logos and the quote that follows opens an
+        // ordinary (unterminated) string literal.
+        let tokens = tokenize_with_dialect(sql, &GenericDialect);
+        assert!(!tokens.iter().any(|t| t.kind == TokenKind::DollarString));
+    }
+
+    #[test]
+    fn test_dollar_quoted_string_with_tag() {
+        use crate::dialect::PostgreSqlDialect;
+
+        let sql = "SELECT $tag$a $$ nested $ dollar$tag$";
+        let tokens = tokenize_with_dialect(sql, &PostgreSqlDialect);
+        let s = tokens
+            .iter()
+            .find(|t| t.kind == TokenKind::DollarString)
+            .expect("tagged dollar-quote should be one token");
+        assert_eq!(s.value(), "a $$ nested $ dollar");
+    }
+
+    #[test]
+    fn test_colon_colon_is_a_single_token() {
+        let sql = "SELECT age::text";
+        let tokens = tokenize(sql);
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::ColonColon));
+    }
 }
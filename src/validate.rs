@@ -0,0 +1,265 @@
+// Chapter 8: Parse != Permit - Allowlist Validation
+// A permissive grammar parses anything; this pass decides what an untrusted
+// user is actually allowed to ask for, before the query reaches a backend.
+
+use crate::ast::{Query, SelectStmt, Statement, TableRef};
+use crate::expr::Expr;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Why an identifier (or whole statement) was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    /// A table reference not on the allowlist.
+    Table,
+    /// A column reference not allowed for its table.
+    Column,
+    /// A non-`SELECT` statement (INSERT/UPDATE/DELETE/...).
+    NonSelect,
+}
+
+/// A single allowlist violation, anchored at the offending identifier.
+///
+/// Column references carry no span of their own in this AST, so a disallowed
+/// column is anchored at the start of the `SELECT` that introduced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub identifier: String,
+    pub kind: Rejection,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// An allowlist of tables and, per table, the columns a query may reference.
+///
+/// Built up with [`Validator::allow_table`], then applied with
+/// [`Validator::validate`]. Any table not explicitly allowed is rejected, and so
+/// is any column not allowed for its table. Column references are not checked
+/// in a `SELECT` without a `FROM` clause or whose `FROM` names a CTE.
+#[derive(Debug, Clone, Default)]
+pub struct Validator {
+    tables: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow `table` and the given `columns` on it. Chainable.
+    pub fn allow_table(mut self, table: &str, columns: &[&str]) -> Self {
+        self.tables.insert(
+            table.to_string(),
+            columns.iter().map(|c| c.to_string()).collect(),
+        );
+        self
+    }
+
+    /// Validate `stmt`, returning every violation found. An empty `Ok` means
+    /// the statement only touches allowlisted tables and columns and is a
+    /// read-only `SELECT`.
+    pub fn validate(&self, stmt: &Statement<'_>) -> Result<(), Vec<Violation>> {
+        let mut violations = Vec::new();
+        match stmt {
+            Statement::Query(query) => {
+                let mut cte_scope: Vec<String> = Vec::new();
+                self.check_query(query, &mut cte_scope, &mut violations);
+            }
+            // Anything that can mutate data is rejected outright.
+            other => violations.push(Violation {
+                identifier: statement_keyword(other).to_string(),
+                kind: Rejection::NonSelect,
+                line: other.span().start.line,
+                column: other.span().start.column,
+            }),
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    fn check_query(
+        &self,
+        query: &Query<'_>,
+        cte_scope: &mut Vec<String>,
+        violations: &mut Vec<Violation>,
+    ) {
+        match query {
+            Query::Select(select) => self.check_select(select, cte_scope, violations),
+            Query::Union { left, right, .. } => {
+                self.check_query(left, cte_scope, violations);
+                self.check_query(right, cte_scope, violations);
+            }
+            Query::With { with, query } => {
+                let scope_len = cte_scope.len();
+                for cte in &with.ctes {
+                    // A CTE name is itself a valid table reference downstream.
+                    cte_scope.push(cte.name.to_string());
+                    self.check_query(&cte.query, cte_scope, violations);
+                }
+                self.check_query(query, cte_scope, violations);
+                cte_scope.truncate(scope_len);
+            }
+        }
+    }
+
+    fn check_select(
+        &self,
+        select: &SelectStmt<'_>,
+        cte_scope: &[String],
+        violations: &mut Vec<Violation>,
+    ) {
+        // Resolve the FROM table; a CTE reference is permitted, a real table
+        // must be on the allowlist. Column checks only apply to allowlisted
+        // tables, since a CTE's columns aren't known here.
+        let allowed_columns = match &select.from {
+            Some(table) => self.check_table(table, cte_scope, violations),
+            None => None,
+        };
+
+        for expr in &select.projection {
+            self.check_expr(expr, select, allowed_columns, violations);
+        }
+        if let Some(where_clause) = &select.where_clause {
+            self.check_expr(where_clause, select, allowed_columns, violations);
+        }
+    }
+
+    /// Validate a table reference, returning the allowed columns to enforce on
+    /// the enclosing SELECT (or `None` when columns can't be constrained).
+    fn check_table<'v>(
+        &'v self,
+        table: &TableRef<'_>,
+        cte_scope: &[String],
+        violations: &mut Vec<Violation>,
+    ) -> Option<&'v BTreeSet<String>> {
+        if cte_scope.iter().any(|name| name == table.name) {
+            return None;
+        }
+        match self.tables.get(table.name) {
+            Some(columns) => Some(columns),
+            None => {
+                violations.push(Violation {
+                    identifier: table.name.to_string(),
+                    kind: Rejection::Table,
+                    line: table.span.start.line,
+                    column: table.span.start.column,
+                });
+                None
+            }
+        }
+    }
+
+    fn check_expr(
+        &self,
+        expr: &Expr<'_>,
+        select: &SelectStmt<'_>,
+        allowed_columns: Option<&BTreeSet<String>>,
+        violations: &mut Vec<Violation>,
+    ) {
+        match expr {
+            Expr::Column(name) => {
+                if let Some(columns) = allowed_columns {
+                    if !columns.contains(*name) {
+                        violations.push(Violation {
+                            identifier: name.to_string(),
+                            kind: Rejection::Column,
+                            line: select.span.start.line,
+                            column: select.span.start.column,
+                        });
+                    }
+                }
+            }
+            Expr::Binary { left, right, .. } => {
+                self.check_expr(left, select, allowed_columns, violations);
+                self.check_expr(right, select, allowed_columns, violations);
+            }
+            Expr::Unary { operand, .. } => {
+                self.check_expr(operand, select, allowed_columns, violations)
+            }
+            Expr::Call { args, .. } => {
+                for arg in args {
+                    self.check_expr(arg, select, allowed_columns, violations);
+                }
+            }
+            Expr::InList { expr, list, .. } => {
+                self.check_expr(expr, select, allowed_columns, violations);
+                for item in list {
+                    self.check_expr(item, select, allowed_columns, violations);
+                }
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => {
+                self.check_expr(expr, select, allowed_columns, violations);
+                self.check_expr(low, select, allowed_columns, violations);
+                self.check_expr(high, select, allowed_columns, violations);
+            }
+            Expr::Paren(inner) => self.check_expr(inner, select, allowed_columns, violations),
+            Expr::Cast { expr, .. } => {
+                self.check_expr(expr, select, allowed_columns, violations)
+            }
+            Expr::Literal(_) | Expr::Star | Expr::Error => {}
+        }
+    }
+}
+
+/// The leading keyword describing a non-SELECT statement, for diagnostics.
+fn statement_keyword(stmt: &Statement<'_>) -> &'static str {
+    match stmt {
+        Statement::Insert(_) => "INSERT",
+        Statement::Update(_) => "UPDATE",
+        Statement::Delete(_) => "DELETE",
+        Statement::Error => "<error>",
+        Statement::Query(_) => "SELECT",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Backtrace;
+    use crate::parser::Parser;
+    use crate::token::tokenize;
+
+    fn validator() -> Validator {
+        Validator::new().allow_table("users", &["id", "name"])
+    }
+
+    fn check(validator: &Validator, sql: &str) -> Result<(), Vec<Violation>> {
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, sql);
+        let stmt = parser.parse_statement().expect("should parse");
+        validator.validate(&stmt)
+    }
+
+    #[test]
+    fn test_allowed_query_passes() {
+        assert!(check(&validator(), "SELECT id, name FROM users WHERE id = 1").is_ok());
+    }
+
+    #[test]
+    fn test_disallowed_table_is_rejected() {
+        let err = check(&validator(), "SELECT id FROM secrets").unwrap_err();
+        assert_eq!(err.len(), 1);
+        assert_eq!(err[0].kind, Rejection::Table);
+        assert_eq!(err[0].identifier, "secrets");
+    }
+
+    #[test]
+    fn test_disallowed_column_is_rejected() {
+        let err = check(&validator(), "SELECT password FROM users").unwrap_err();
+        assert_eq!(err.len(), 1);
+        assert_eq!(err[0].kind, Rejection::Column);
+        assert_eq!(err[0].identifier, "password");
+    }
+
+    #[test]
+    fn test_non_select_is_rejected() {
+        let err = check(&validator(), "DELETE FROM users WHERE id = 1").unwrap_err();
+        assert_eq!(err[0].kind, Rejection::NonSelect);
+    }
+}
@@ -0,0 +1,362 @@
+// Chapter 7: Walking the Tree - Visitors for Analysis and Rewriting
+// Parsing builds the tree; a visitor is the reusable way to traverse it so
+// every consumer doesn't re-implement recursion over Query/SelectStmt/Expr.
+
+use crate::ast::{InsertSource, Query, SelectStmt, Statement, TableRef, With, CTE};
+use crate::eval::{eval, Value};
+use crate::expr::{Expr, Literal};
+
+/// A read-only traversal over the AST.
+///
+/// Every method has a default body that recurses into the node's children via
+/// the matching `walk_*` free function, so an implementor only overrides the
+/// nodes it cares about and still gets a full walk for free.
+pub trait Visitor<'a> {
+    fn visit_statement(&mut self, stmt: &Statement<'a>) {
+        walk_statement(self, stmt);
+    }
+    fn visit_query(&mut self, query: &Query<'a>) {
+        walk_query(self, query);
+    }
+    fn visit_select(&mut self, select: &SelectStmt<'a>) {
+        walk_select(self, select);
+    }
+    fn visit_with(&mut self, with: &With<'a>) {
+        walk_with(self, with);
+    }
+    fn visit_cte(&mut self, cte: &CTE<'a>) {
+        walk_cte(self, cte);
+    }
+    fn visit_table_ref(&mut self, _table: &TableRef<'a>) {}
+    fn visit_expr(&mut self, expr: &Expr<'a>) {
+        walk_expr(self, expr);
+    }
+}
+
+pub fn walk_statement<'a, V: Visitor<'a> + ?Sized>(v: &mut V, stmt: &Statement<'a>) {
+    match stmt {
+        Statement::Query(query) => v.visit_query(query),
+        Statement::Insert(insert) => match &insert.source {
+            InsertSource::Values(rows) => {
+                for row in rows {
+                    for expr in row {
+                        v.visit_expr(expr);
+                    }
+                }
+            }
+            InsertSource::Query(query) => v.visit_query(query),
+        },
+        Statement::Update(update) => {
+            for (_, expr) in &update.assignments {
+                v.visit_expr(expr);
+            }
+            if let Some(where_clause) = &update.where_clause {
+                v.visit_expr(where_clause);
+            }
+        }
+        Statement::Delete(delete) => {
+            if let Some(where_clause) = &delete.where_clause {
+                v.visit_expr(where_clause);
+            }
+        }
+        Statement::Error => {}
+    }
+}
+
+pub fn walk_query<'a, V: Visitor<'a> + ?Sized>(v: &mut V, query: &Query<'a>) {
+    match query {
+        Query::Select(select) => v.visit_select(select),
+        Query::With { with, query } => {
+            v.visit_with(with);
+            v.visit_query(query);
+        }
+        Query::Union { left, right, .. } => {
+            v.visit_query(left);
+            v.visit_query(right);
+        }
+    }
+}
+
+pub fn walk_select<'a, V: Visitor<'a> + ?Sized>(v: &mut V, select: &SelectStmt<'a>) {
+    for expr in &select.projection {
+        v.visit_expr(expr);
+    }
+    if let Some(from) = &select.from {
+        v.visit_table_ref(from);
+    }
+    if let Some(where_clause) = &select.where_clause {
+        v.visit_expr(where_clause);
+    }
+}
+
+pub fn walk_with<'a, V: Visitor<'a> + ?Sized>(v: &mut V, with: &With<'a>) {
+    for cte in &with.ctes {
+        v.visit_cte(cte);
+    }
+}
+
+pub fn walk_cte<'a, V: Visitor<'a> + ?Sized>(v: &mut V, cte: &CTE<'a>) {
+    v.visit_query(&cte.query);
+}
+
+pub fn walk_expr<'a, V: Visitor<'a> + ?Sized>(v: &mut V, expr: &Expr<'a>) {
+    match expr {
+        Expr::Binary { left, right, .. } => {
+            v.visit_expr(left);
+            v.visit_expr(right);
+        }
+        Expr::Unary { operand, .. } => v.visit_expr(operand),
+        Expr::Call { args, .. } => {
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        Expr::InList { expr, list, .. } => {
+            v.visit_expr(expr);
+            for item in list {
+                v.visit_expr(item);
+            }
+        }
+        Expr::Between {
+            expr, low, high, ..
+        } => {
+            v.visit_expr(expr);
+            v.visit_expr(low);
+            v.visit_expr(high);
+        }
+        Expr::Paren(inner) => v.visit_expr(inner),
+        Expr::Cast { expr, .. } => v.visit_expr(expr),
+        Expr::Column(_) | Expr::Literal(_) | Expr::Star | Expr::Error => {}
+    }
+}
+
+/// A mutable traversal used for in-place tree rewriting. The `walk_*` helpers
+/// recurse into children first, so an override sees already-rewritten
+/// subtrees (a bottom-up rewrite).
+pub trait VisitorMut<'a> {
+    fn visit_statement(&mut self, stmt: &mut Statement<'a>) {
+        walk_statement_mut(self, stmt);
+    }
+    fn visit_query(&mut self, query: &mut Query<'a>) {
+        walk_query_mut(self, query);
+    }
+    fn visit_select(&mut self, select: &mut SelectStmt<'a>) {
+        walk_select_mut(self, select);
+    }
+    fn visit_with(&mut self, with: &mut With<'a>) {
+        walk_with_mut(self, with);
+    }
+    fn visit_cte(&mut self, cte: &mut CTE<'a>) {
+        walk_cte_mut(self, cte);
+    }
+    fn visit_table_ref(&mut self, _table: &mut TableRef<'a>) {}
+    fn visit_expr(&mut self, expr: &mut Expr<'a>) {
+        walk_expr_mut(self, expr);
+    }
+}
+
+pub fn walk_statement_mut<'a, V: VisitorMut<'a> + ?Sized>(v: &mut V, stmt: &mut Statement<'a>) {
+    match stmt {
+        Statement::Query(query) => v.visit_query(query),
+        Statement::Insert(insert) => match &mut insert.source {
+            InsertSource::Values(rows) => {
+                for row in rows {
+                    for expr in row {
+                        v.visit_expr(expr);
+                    }
+                }
+            }
+            InsertSource::Query(query) => v.visit_query(query),
+        },
+        Statement::Update(update) => {
+            for (_, expr) in &mut update.assignments {
+                v.visit_expr(expr);
+            }
+            if let Some(where_clause) = &mut update.where_clause {
+                v.visit_expr(where_clause);
+            }
+        }
+        Statement::Delete(delete) => {
+            if let Some(where_clause) = &mut delete.where_clause {
+                v.visit_expr(where_clause);
+            }
+        }
+        Statement::Error => {}
+    }
+}
+
+pub fn walk_query_mut<'a, V: VisitorMut<'a> + ?Sized>(v: &mut V, query: &mut Query<'a>) {
+    match query {
+        Query::Select(select) => v.visit_select(select),
+        Query::With { with, query } => {
+            v.visit_with(with);
+            v.visit_query(query);
+        }
+        Query::Union { left, right, .. } => {
+            v.visit_query(left);
+            v.visit_query(right);
+        }
+    }
+}
+
+pub fn walk_select_mut<'a, V: VisitorMut<'a> + ?Sized>(v: &mut V, select: &mut SelectStmt<'a>) {
+    for expr in &mut select.projection {
+        v.visit_expr(expr);
+    }
+    if let Some(from) = &mut select.from {
+        v.visit_table_ref(from);
+    }
+    if let Some(where_clause) = &mut select.where_clause {
+        v.visit_expr(where_clause);
+    }
+}
+
+pub fn walk_with_mut<'a, V: VisitorMut<'a> + ?Sized>(v: &mut V, with: &mut With<'a>) {
+    for cte in &mut with.ctes {
+        v.visit_cte(cte);
+    }
+}
+
+pub fn walk_cte_mut<'a, V: VisitorMut<'a> + ?Sized>(v: &mut V, cte: &mut CTE<'a>) {
+    v.visit_query(&mut cte.query);
+}
+
+pub fn walk_expr_mut<'a, V: VisitorMut<'a> + ?Sized>(v: &mut V, expr: &mut Expr<'a>) {
+    match expr {
+        Expr::Binary { left, right, .. } => {
+            v.visit_expr(left);
+            v.visit_expr(right);
+        }
+        Expr::Unary { operand, .. } => v.visit_expr(operand),
+        Expr::Call { args, .. } => {
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        Expr::InList { expr, list, .. } => {
+            v.visit_expr(expr);
+            for item in list {
+                v.visit_expr(item);
+            }
+        }
+        Expr::Between {
+            expr, low, high, ..
+        } => {
+            v.visit_expr(expr);
+            v.visit_expr(low);
+            v.visit_expr(high);
+        }
+        Expr::Paren(inner) => v.visit_expr(inner),
+        Expr::Cast { expr, .. } => v.visit_expr(expr),
+        Expr::Column(_) | Expr::Literal(_) | Expr::Star | Expr::Error => {}
+    }
+}
+
+/// Collects the table name of every `FROM` clause and every column reference
+/// in an expression. The target table of an INSERT/UPDATE/DELETE and the
+/// column names listed by INSERT or assigned by UPDATE are not collected.
+///
+/// A small built-in visitor useful for dependency extraction and linting.
+#[derive(Debug, Default)]
+pub struct ReferenceCollector<'a> {
+    pub tables: Vec<&'a str>,
+    pub columns: Vec<&'a str>,
+}
+
+impl<'a> ReferenceCollector<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<'a> Visitor<'a> for ReferenceCollector<'a> {
+    fn visit_table_ref(&mut self, table: &TableRef<'a>) {
+        self.tables.push(table.name);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr<'a>) {
+        if let Expr::Column(name) = expr {
+            self.columns.push(name);
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Folds constant sub-expressions: a `Binary` whose operands are both literals
+/// is replaced by the literal it evaluates to. Runs bottom-up, so nested
+/// constants collapse in a single pass (e.g. `2 + 3 * 4` → `14`).
+#[derive(Debug, Default)]
+pub struct ConstantFolder;
+
+impl<'a> VisitorMut<'a> for ConstantFolder {
+    fn visit_expr(&mut self, expr: &mut Expr<'a>) {
+        // Fold children first so the parent sees collapsed literals.
+        walk_expr_mut(self, expr);
+
+        if is_binary_of_literals(expr) {
+            if let Some(lit) = eval(expr).ok().and_then(value_to_literal) {
+                *expr = Expr::Literal(lit);
+            }
+        }
+    }
+}
+
+/// Whether `expr` is a binary operation whose operands are both literals, and
+/// therefore a candidate for constant folding.
+fn is_binary_of_literals(expr: &Expr<'_>) -> bool {
+    matches!(
+        expr,
+        Expr::Binary { left, right, .. }
+            if matches!(**left, Expr::Literal(_)) && matches!(**right, Expr::Literal(_))
+    )
+}
+
+/// Convert an evaluated `Value` back into a `Literal`, where one exists.
+/// Booleans have no literal form, so they are left unfolded.
+fn value_to_literal<'a>(value: Value<'a>) -> Option<Literal<'a>> {
+    match value {
+        Value::Int(n) => Some(Literal::Number(n)),
+        Value::Float(f) => Some(Literal::Float(f)),
+        Value::Str(s) => Some(Literal::String(s)),
+        Value::Bool(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Backtrace;
+    use crate::parser::Parser;
+    use crate::token::tokenize;
+
+    #[test]
+    fn test_reference_collector() {
+        let sql = "SELECT id, name FROM users WHERE age > 18";
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, sql);
+        let stmt = parser.parse_statement().unwrap();
+
+        let mut collector = ReferenceCollector::new();
+        collector.visit_statement(&stmt);
+        assert_eq!(collector.tables, vec!["users"]);
+        assert!(collector.columns.contains(&"id"));
+        assert!(collector.columns.contains(&"age"));
+    }
+
+    #[test]
+    fn test_constant_folder() {
+        let sql = "SELECT 2 + 3 * 4 FROM t";
+        let tokens = tokenize(sql);
+        let backtrace = Backtrace::new();
+        let mut parser = Parser::new(&tokens, &backtrace, sql);
+        let mut stmt = parser.parse_statement().unwrap();
+
+        ConstantFolder.visit_statement(&mut stmt);
+        if let Statement::Query(Query::Select(select)) = &stmt {
+            assert_eq!(select.projection[0], Expr::Literal(Literal::Number(14)));
+        } else {
+            panic!("expected a SELECT");
+        }
+    }
+}
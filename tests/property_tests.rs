@@ -1,7 +1,23 @@
 // Property-based tests using proptest
 use proptest::prelude::*;
-use sql_parser_demo::parser::{parse_sql, parse_sql_to_string};
-use sql_parser_demo::token::tokenize;
+use sql_parser_demo::dialect::{
+    AnsiDialect, Dialect, GenericDialect, MySqlDialect, PostgreSqlDialect,
+};
+use sql_parser_demo::parser::{parse_sql, parse_sql_owned, parse_sql_to_string, parse_sql_with};
+use sql_parser_demo::token::{tokenize, TokenKind};
+#[cfg(feature = "serde")]
+use sql_parser_demo::OwnedStatement;
+
+/// Pick one of the built-in dialects by index, so a property can run the same
+/// SQL across every dialect the crate supports.
+fn dialect_for(index: usize) -> &'static dyn Dialect {
+    match index % 4 {
+        0 => &GenericDialect,
+        1 => &AnsiDialect,
+        2 => &MySqlDialect,
+        _ => &PostgreSqlDialect,
+    }
+}
 
 // Strategy for generating valid SQL identifiers (excluding SQL keywords)
 fn identifier_strategy() -> impl Strategy<Value = String> {
@@ -86,6 +102,24 @@ fn where_clause_strategy() -> impl Strategy<Value = String> {
 }
 
 proptest! {
+    #[test]
+    fn test_raw_string_is_one_token_without_escape_processing(
+        body in r"[a-zA-Z0-9 (),.*]{0,20}"
+    ) {
+        // Backslashes inside a raw literal must survive as-is.
+        let sql = format!(r"R'f\({}\)'", body);
+        let tokens = tokenize(&sql);
+        let raw: Vec<_> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::RawString)
+            .collect();
+        prop_assert_eq!(raw.len(), 1);
+        // Zero-copy: the token still points at the exact source substring.
+        prop_assert_eq!(raw[0].text, sql.as_str());
+        // No escape processing: the decoded value is the body verbatim.
+        prop_assert_eq!(raw[0].value().into_owned(), format!(r"f\({}\)", body));
+    }
+
     #[test]
     fn test_tokenizer_never_panics(input in ".*") {
         // The tokenizer should never panic on any input
@@ -98,6 +132,20 @@ proptest! {
         let _ = parse_sql(&input);
     }
 
+    #[test]
+    fn test_reprint_round_trips(
+        col in identifier_strategy(),
+        table in identifier_strategy(),
+        where_clause in where_clause_strategy()
+    ) {
+        // parse → to_sql → parse must be idempotent: the canonical reprint
+        // yields an AST equal to the original.
+        let sql = format!("SELECT {} FROM {} WHERE {}", col, table, where_clause);
+        let first = parse_sql_owned(&sql).expect("valid SQL should parse");
+        let second = parse_sql_owned(&first.to_sql()).expect("reprint should parse");
+        prop_assert_eq!(first, second);
+    }
+
     #[test]
     fn test_valid_select_always_parses(
         col in identifier_strategy(),
@@ -107,6 +155,35 @@ proptest! {
         prop_assert!(parse_sql(&sql).is_ok());
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_owned_ast_serde_round_trips(
+        col in identifier_strategy(),
+        table in identifier_strategy(),
+        where_clause in where_clause_strategy()
+    ) {
+        // parse → to JSON → from JSON must be lossless: the deserialized AST
+        // is equal to the one that went in.
+        let sql = format!("SELECT {} FROM {} WHERE {}", col, table, where_clause);
+        let first = parse_sql_owned(&sql).expect("valid SQL should parse");
+        let json = serde_json::to_string(&first).expect("owned AST should serialize");
+        let second: OwnedStatement =
+            serde_json::from_str(&json).expect("serialized AST should deserialize");
+        prop_assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_valid_select_parses_under_every_dialect(
+        col in identifier_strategy(),
+        table in identifier_strategy(),
+        dialect in 0usize..4
+    ) {
+        // A plain SELECT is valid in every dialect, so the dialect parameter
+        // must never change the outcome.
+        let sql = format!("SELECT {} FROM {}", col, table);
+        prop_assert!(parse_sql_with(&sql, dialect_for(dialect)).is_ok());
+    }
+
     #[test]
     fn test_valid_select_with_where_always_parses(
         col in identifier_strategy(),
@@ -183,6 +260,33 @@ proptest! {
         prop_assert!(parse_sql(&sql).is_ok());
     }
 
+    #[test]
+    fn test_parens_within_limit_parse(depth in 1usize..100) {
+        // Nesting comfortably below the default recursion limit (128) parses.
+        let mut expr = "1".to_string();
+        for _ in 0..depth {
+            expr = format!("({})", expr);
+        }
+        let sql = format!("SELECT {}", expr);
+        prop_assert!(parse_sql(&sql).is_ok());
+    }
+
+    #[test]
+    fn test_parens_beyond_limit_error_not_panic(extra in 1usize..500) {
+        // Nesting past the limit must return the recursion-limit error rather
+        // than overflow the stack — proptest can't reach this depth on its own.
+        let depth = 128 + extra;
+        let mut expr = "1".to_string();
+        for _ in 0..depth {
+            expr = format!("({})", expr);
+        }
+        let sql = format!("SELECT {}", expr);
+        match parse_sql(&sql) {
+            Ok(()) => prop_assert!(false, "expected a recursion-limit error"),
+            Err(error) => prop_assert!(error.is_recursion_limit_exceeded()),
+        }
+    }
+
     #[test]
     fn test_binary_operators_associativity(
         a in identifier_strategy(),
@@ -236,8 +340,8 @@ proptest! {
 
         // All non-EOF tokens should point to substrings of the original input
         for token in &tokens {
-            if token.text != "" {  // Skip EOF token
-                let substring = &sql[token.span.clone()];
+            if !token.text.is_empty() {  // Skip EOF token
+                let substring = &sql[token.byte_range()];
                 prop_assert_eq!(substring, token.text);
             }
         }
@@ -262,26 +366,29 @@ proptest! {
 proptest! {
     #[test]
     fn test_typo_suggestions_are_reasonable(
-        keyword in prop::sample::select(vec!["SELECT", "FROM", "WHERE", "WITH"]),
+        // The keywords the parser tries at the start of a statement, other
+        // than SELECT itself: SELECT has its own lenient "starts with SEL"
+        // typo tolerance (see `parse_select`) that can swallow the error
+        // entirely, which isn't what this property is about.
+        keyword in prop::sample::select(vec!["INSERT", "UPDATE", "DELETE", "WITH"]),
         typo_char in prop::char::range('A', 'Z')
     ) {
-        // Create a typo by replacing one character
+        let idx = keyword.len() / 2;
+        let original = keyword.as_bytes()[idx].to_ascii_uppercase();
+        // A substitution that happens to pick the same letter isn't a typo.
+        prop_assume!(typo_char as u8 != original);
+
         let mut typo = keyword.to_string();
-        if !typo.is_empty() {
-            let idx = typo.len() / 2;
-            typo.replace_range(idx..idx+1, &typo_char.to_string());
-
-            let sql = format!("{} * FROM users", typo);
-
-            if let Err(error) = parse_sql(&sql) {
-                // If there's a suggestion, it should be somewhat close to the original
-                if let Some(suggestion) = error.suggestion {
-                    // The suggestion should be a valid SQL keyword
-                    let valid_keywords = vec!["SELECT", "FROM", "WHERE", "WITH", "INSERT", "UPDATE", "DELETE"];
-                    prop_assert!(valid_keywords.contains(&suggestion.as_str()));
-                }
-            }
-        }
+        typo.replace_range(idx..idx + 1, &typo_char.to_string());
+
+        let sql = format!("{} * FROM users", typo);
+
+        let error = parse_sql(&sql).expect_err("a single-character keyword typo should not parse");
+        // A single-character typo always stays above the Jaro-Winkler
+        // similarity threshold for the keyword it came from, and no closer to
+        // any other keyword in the
+        // expected set, so the suggestion must name it exactly.
+        prop_assert_eq!(error.suggestion, Some(keyword.to_string()));
     }
 
     #[test]